@@ -29,6 +29,9 @@ pub enum ERC3643Error {
     #[msg("Identity not found")]
     IdentityNotFound,
     
+    #[msg("Identity registry is not bound to this identity storage")]
+    IdentityStorageMismatch,
+    
     #[msg("Claim not found")]
     ClaimNotFound,
     
@@ -38,6 +41,12 @@ pub enum ERC3643Error {
     #[msg("Invalid claim signature")]
     InvalidClaimSignature,
     
+    #[msg("Unsupported claim scheme")]
+    UnsupportedClaimScheme,
+    
+    #[msg("Missing Ed25519 signature verification instruction")]
+    MissingSignatureVerification,
+    
     #[msg("Claim expired")]
     ClaimExpired,
     
@@ -80,6 +89,66 @@ pub enum ERC3643Error {
     #[msg("Issuer role required")]
     IssuerRoleRequired,
     
+    #[msg("Compliance manager role required")]
+    ComplianceManagerRoleRequired,
+
+    #[msg("Supply agent role required")]
+    SupplyAgentRoleRequired,
+
+    #[msg("Freeze agent role required")]
+    FreezeAgentRoleRequired,
+
+    #[msg("Transfer agent role required")]
+    TransferAgentRoleRequired,
+
+    #[msg("Recovery agent role required")]
+    RecoveryAgentRoleRequired,
+
+    #[msg("Identity agent role required")]
+    IdentityAgentRoleRequired,
+
+    #[msg("Role already granted")]
+    RoleAlreadyGranted,
+
+    #[msg("Role not granted")]
+    RoleNotGranted,
+
+    #[msg("Too many role members")]
+    TooManyRoleMembers,
+
+    #[msg("Action requires a multisig proposal")]
+    MultisigRequired,
+
+    #[msg("Invalid multisig threshold")]
+    InvalidMultisigThreshold,
+
+    #[msg("Too many multisig approvers")]
+    TooManyApprovers,
+
+    #[msg("Signer is not a multisig approver")]
+    NotAnApprover,
+
+    #[msg("Proposal already approved by signer")]
+    ProposalAlreadyApproved,
+
+    #[msg("Proposal approval threshold not met")]
+    ProposalThresholdNotMet,
+
+    #[msg("Proposal expired")]
+    ProposalExpired,
+
+    #[msg("Proposal not expired")]
+    ProposalNotExpired,
+
+    #[msg("Compliance changes must be queued behind the timelock")]
+    TimelockRequired,
+
+    #[msg("Compliance timelock is not enabled")]
+    TimelockNotEnabled,
+
+    #[msg("Compliance timelock has not elapsed")]
+    TimelockNotElapsed,
+    
     #[msg("Invalid mint authority")]
     InvalidMintAuthority,
     
@@ -154,10 +223,25 @@ pub enum ERC3643Error {
     
     #[msg("Compliance module not found")]
     ComplianceModuleNotFound,
+
+    #[msg("Invalid compliance module")]
+    InvalidComplianceModule,
+
+    #[msg("Compliance module accounts missing or mismatched")]
+    ComplianceModuleAccountsMismatch,
+
+    #[msg("Compliance module returned an invalid response")]
+    InvalidComplianceModuleResponse,
     
     #[msg("Invalid compliance configuration")]
     InvalidComplianceConfiguration,
     
+    #[msg("Compliance rule limit reached")]
+    ComplianceRuleLimitReached,
+    
+    #[msg("Compliance rule not found")]
+    ComplianceRuleNotFound,
+    
     #[msg("Transfer limit exceeded")]
     TransferLimitExceeded,
     
@@ -167,9 +251,21 @@ pub enum ERC3643Error {
     #[msg("Monthly limit exceeded")]
     MonthlyLimitExceeded,
     
+    #[msg("Transfer counter not found")]
+    TransferCounterNotFound,
+    
     #[msg("Country not allowed")]
     CountryNotAllowed,
     
+    #[msg("Transfer requires issuer approval")]
+    TransferRequiresApproval,
+    
+    #[msg("Transfer request expired")]
+    TransferRequestExpired,
+    
+    #[msg("Transfer request not expired")]
+    TransferRequestNotExpired,
+    
     #[msg("Investor limit reached")]
     InvestorLimitReached,
     
@@ -178,6 +274,9 @@ pub enum ERC3643Error {
     
     #[msg("Maximum holding exceeded")]
     MaximumHoldingExceeded,
+
+    #[msg("Maximum supply exceeded")]
+    MaxSupplyExceeded,
     
     #[msg("Lock period active")]
     LockPeriodActive,
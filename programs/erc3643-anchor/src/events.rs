@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::{AdminAction, ComplianceAction, LockType, RestrictionType, Role};
 
 /// Event emitted when token information is updated
 #[event]
@@ -78,6 +79,22 @@ pub struct IdentityRemoved {
     pub identity: Pubkey,
 }
 
+/// Event emitted when a token identity registry is bound to an identity storage
+#[event]
+pub struct IdentityRegistryBound {
+    pub identity_storage: Pubkey,
+    pub identity_registry: Pubkey,
+    pub mint: Pubkey,
+}
+
+/// Event emitted when a token identity registry is unbound from an identity storage
+#[event]
+pub struct IdentityRegistryUnbound {
+    pub identity_storage: Pubkey,
+    pub identity_registry: Pubkey,
+    pub mint: Pubkey,
+}
+
 /// Event emitted when identity is updated
 #[event]
 pub struct IdentityUpdated {
@@ -190,6 +207,7 @@ pub struct ComplianceCheck {
 #[event]
 pub struct AgentAdded {
     pub agent: Pubkey,
+    pub role: Role,
     pub added_by: Pubkey,
 }
 
@@ -197,6 +215,7 @@ pub struct AgentAdded {
 #[event]
 pub struct AgentRemoved {
     pub agent: Pubkey,
+    pub role: Role,
     pub removed_by: Pubkey,
 }
 
@@ -207,6 +226,104 @@ pub struct OwnershipTransferred {
     pub new_owner: Pubkey,
 }
 
+/// Event emitted when a new owner is nominated
+#[event]
+pub struct OwnershipTransferStarted {
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+/// Event emitted when a pending ownership transfer is cancelled
+#[event]
+pub struct OwnershipTransferCancelled {
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+/// Event emitted when a multisig takes over sensitive owner actions
+#[event]
+pub struct MultisigCreated {
+    pub mint: Pubkey,
+    pub multisig: Pubkey,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+/// Event emitted when the multisig approver set changes
+#[event]
+pub struct ApproversUpdated {
+    pub mint: Pubkey,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+/// Event emitted when a proposal is created
+#[event]
+pub struct ProposalCreated {
+    pub mint: Pubkey,
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: AdminAction,
+    pub expires_at: i64,
+}
+
+/// Event emitted when a proposal is approved
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub approver: Pubkey,
+    pub approvals: u8,
+}
+
+/// Event emitted when a proposal is executed
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub executed_by: Pubkey,
+}
+
+/// Event emitted when a proposal is cancelled or an expired one is closed
+#[event]
+pub struct ProposalCancelled {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub cancelled_by: Pubkey,
+}
+
+/// Event emitted when the compliance manager is set
+#[event]
+pub struct ComplianceManagerSet {
+    pub previous_manager: Pubkey,
+    pub new_manager: Pubkey,
+}
+
+/// Event emitted when a role is granted
+#[event]
+pub struct RoleGranted {
+    pub mint: Pubkey,
+    pub member: Pubkey,
+    pub role: Role,
+    pub granted_by: Pubkey,
+}
+
+/// Event emitted when a role is revoked
+#[event]
+pub struct RoleRevoked {
+    pub mint: Pubkey,
+    pub member: Pubkey,
+    pub role: Role,
+    pub revoked_by: Pubkey,
+}
+
+/// Event emitted when the supply cap changes
+#[event]
+pub struct MaxSupplyUpdated {
+    pub mint: Pubkey,
+    pub max_supply: Option<u64>,
+}
+
 /// Event emitted when mint authority is transferred
 #[event]
 pub struct MintAuthorityTransferred {
@@ -257,3 +374,181 @@ pub struct ComplianceModuleRemoved {
     pub module: Pubkey,
     pub module_type: String,
 }
+
+/// Event emitted when a lock period is added
+#[event]
+pub struct LockPeriodAdded {
+    pub mint: Pubkey,
+    pub index: u8,
+    pub user: Option<Pubkey>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub lock_type: LockType,
+}
+
+/// Event emitted when a lock period is updated
+#[event]
+pub struct LockPeriodUpdated {
+    pub mint: Pubkey,
+    pub index: u8,
+    pub user: Option<Pubkey>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub lock_type: LockType,
+}
+
+/// Event emitted when a lock period is removed
+#[event]
+pub struct LockPeriodRemoved {
+    pub mint: Pubkey,
+    pub index: u8,
+    pub user: Option<Pubkey>,
+}
+
+/// Event emitted when a country restriction is added
+#[event]
+pub struct CountryRestrictionAdded {
+    pub mint: Pubkey,
+    pub country_code: u16,
+    pub restriction_type: RestrictionType,
+}
+
+/// Event emitted when a country restriction is removed
+#[event]
+pub struct CountryRestrictionRemoved {
+    pub mint: Pubkey,
+    pub country_code: u16,
+}
+
+/// Event emitted when transfer limits are updated
+#[event]
+pub struct TransferLimitsUpdated {
+    pub mint: Pubkey,
+    pub min_transfer_amount: Option<u64>,
+    pub max_transfer_amount: Option<u64>,
+    pub daily_limit: Option<u64>,
+    pub monthly_limit: Option<u64>,
+    pub reset_period: u32,
+}
+
+/// Event emitted when holding limits are updated
+#[event]
+pub struct HoldingLimitsUpdated {
+    pub mint: Pubkey,
+    pub min_holding_amount: Option<u64>,
+    pub max_holding_amount: Option<u64>,
+    pub max_holding_percentage: Option<u16>,
+}
+
+/// Event emitted when a trading window is added
+#[event]
+pub struct TradingWindowAdded {
+    pub mint: Pubkey,
+    pub index: u8,
+    pub start_time: u32,
+    pub end_time: u32,
+    pub days_of_week: u8,
+}
+
+/// Event emitted when a trading window is removed
+#[event]
+pub struct TradingWindowRemoved {
+    pub mint: Pubkey,
+    pub index: u8,
+}
+
+/// Event emitted when the investor cap is updated
+#[event]
+pub struct MaxInvestorsUpdated {
+    pub mint: Pubkey,
+    pub max_investors: Option<u32>,
+}
+
+/// Event emitted when the transfer request lifetime is updated
+#[event]
+pub struct TransferRequestTtlUpdated {
+    pub mint: Pubkey,
+    pub ttl: i64,
+}
+
+/// Event emitted when a holder escrows a transfer for approval
+#[event]
+pub struct TransferRequested {
+    pub request: Pubkey,
+    pub mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+/// Event emitted when a transfer request is approved and released
+#[event]
+pub struct TransferRequestApproved {
+    pub request: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub approved_by: Pubkey,
+}
+
+/// Event emitted when a transfer request is rejected and refunded
+#[event]
+pub struct TransferRequestRejected {
+    pub request: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub rejected_by: Pubkey,
+}
+
+/// Event emitted when an expired transfer request is refunded
+#[event]
+pub struct TransferRequestExpired {
+    pub request: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
+
+/// Event emitted when a country's investor cap is set or removed
+#[event]
+pub struct CountryInvestorLimitUpdated {
+    pub mint: Pubkey,
+    pub country_code: u16,
+    pub max_investors: Option<u32>,
+    pub current_investors: u32,
+}
+
+/// Event emitted when the compliance timelock delay changes
+#[event]
+pub struct ComplianceTimelockUpdated {
+    pub mint: Pubkey,
+    pub delay: i64,
+}
+
+/// Event emitted when a compliance change is queued
+#[event]
+pub struct ComplianceChangeQueued {
+    pub mint: Pubkey,
+    pub change: Pubkey,
+    pub id: u64,
+    pub action: ComplianceAction,
+    pub eta: i64,
+}
+
+/// Event emitted when a queued compliance change is executed
+#[event]
+pub struct ComplianceChangeExecuted {
+    pub mint: Pubkey,
+    pub change: Pubkey,
+    pub id: u64,
+}
+
+/// Event emitted when a queued compliance change is cancelled
+#[event]
+pub struct ComplianceChangeCancelled {
+    pub mint: Pubkey,
+    pub change: Pubkey,
+    pub id: u64,
+}
@@ -1,4 +1,20 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{
+        spl_token_2022::{
+            extension::{BaseStateWithExtensions, StateWithExtensions},
+            state::Mint as MintState,
+        },
+        Token2022,
+    },
+    token_2022_extensions::{
+        spl_token_metadata_interface::{
+            self,
+            state::{Field, TokenMetadata},
+        },
+        token_metadata_update_field, TokenMetadataUpdateField,
+    },
+};
 use crate::{
     error::ERC3643Error,
     events::*,
@@ -37,8 +53,15 @@ pub fn add_trusted_issuer(
     issuer: Pubkey,
     claim_topics: Vec<u64>,
 ) -> Result<()> {
-    let trusted_issuers = &mut ctx.accounts.trusted_issuers_registry;
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_add_trusted_issuer(&mut ctx.accounts.trusted_issuers_registry, issuer, claim_topics)
+}
 
+pub(crate) fn apply_add_trusted_issuer(
+    trusted_issuers: &mut TrustedIssuersRegistry,
+    issuer: Pubkey,
+    claim_topics: Vec<u64>,
+) -> Result<()> {
     // Validate inputs
     validate_pubkey(&issuer)?;
     require!(!claim_topics.is_empty(), ERC3643Error::InvalidArgument);
@@ -85,8 +108,14 @@ pub struct RemoveTrustedIssuer<'info> {
 }
 
 pub fn remove_trusted_issuer(ctx: Context<RemoveTrustedIssuer>, issuer: Pubkey) -> Result<()> {
-    let trusted_issuers = &mut ctx.accounts.trusted_issuers_registry;
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_remove_trusted_issuer(&mut ctx.accounts.trusted_issuers_registry, issuer)
+}
 
+pub(crate) fn apply_remove_trusted_issuer(
+    trusted_issuers: &mut TrustedIssuersRegistry,
+    issuer: Pubkey,
+) -> Result<()> {
     // Validate input
     validate_pubkey(&issuer)?;
 
@@ -116,14 +145,21 @@ pub struct SetAddressFrozen<'info> {
     /// Token configuration
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::FreezeAgent) @ ERC3643Error::FreezeAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Frozen account info
     #[account(
         init_if_needed,
@@ -187,14 +223,21 @@ pub struct FreezePartialTokens<'info> {
     /// Token configuration
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::FreezeAgent) @ ERC3643Error::FreezeAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Frozen account info
     #[account(
         init_if_needed,
@@ -258,14 +301,21 @@ pub struct UnfreezePartialTokens<'info> {
     /// Token configuration
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::FreezeAgent) @ ERC3643Error::FreezeAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Frozen account info
     #[account(
         mut,
@@ -312,32 +362,41 @@ pub struct SetPauseStatus<'info> {
     #[account(
         mut,
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::FreezeAgent) @ ERC3643Error::FreezeAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
 }
 
 pub fn set_pause_status(ctx: Context<SetPauseStatus>, paused: bool) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    let agent = &ctx.accounts.agent;
+    let agent = ctx.accounts.agent.key();
+    apply_set_pause_status(&mut ctx.accounts.config, paused, agent)
+}
 
+pub(crate) fn apply_set_pause_status(config: &mut TokenConfig, paused: bool, by: Pubkey) -> Result<()> {
     // Set pause status
     config.set_paused(paused)?;
 
     if paused {
         emit!(Paused {
-            user_address: agent.key(),
+            user_address: by,
         });
-        msg!("Token paused by: {}", agent.key());
+        msg!("Token paused by: {}", by);
     } else {
         emit!(Unpaused {
-            user_address: agent.key(),
+            user_address: by,
         });
-        msg!("Token unpaused by: {}", agent.key());
+        msg!("Token unpaused by: {}", by);
     }
 
     Ok(())
@@ -371,8 +430,11 @@ pub struct AddClaimTopic<'info> {
 }
 
 pub fn add_claim_topic(ctx: Context<AddClaimTopic>, topic: u64) -> Result<()> {
-    let claim_topics = &mut ctx.accounts.claim_topics_registry;
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_add_claim_topic(&mut ctx.accounts.claim_topics_registry, topic)
+}
 
+pub(crate) fn apply_add_claim_topic(claim_topics: &mut ClaimTopicsRegistry, topic: u64) -> Result<()> {
     // Add the claim topic
     claim_topics.add_topic(topic)?;
 
@@ -413,8 +475,11 @@ pub struct RemoveClaimTopic<'info> {
 }
 
 pub fn remove_claim_topic(ctx: Context<RemoveClaimTopic>, topic: u64) -> Result<()> {
-    let claim_topics = &mut ctx.accounts.claim_topics_registry;
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_remove_claim_topic(&mut ctx.accounts.claim_topics_registry, topic)
+}
 
+pub(crate) fn apply_remove_claim_topic(claim_topics: &mut ClaimTopicsRegistry, topic: u64) -> Result<()> {
     // Remove the claim topic
     claim_topics.remove_topic(topic)?;
 
@@ -427,7 +492,7 @@ pub fn remove_claim_topic(ctx: Context<RemoveClaimTopic>, topic: u64) -> Result<
     Ok(())
 }
 
-/// Add agent
+/// Add an agent, granting it an agent role in the role registry
 #[derive(Accounts)]
 pub struct AddAgent<'info> {
     /// Owner performing the operation
@@ -436,7 +501,6 @@ pub struct AddAgent<'info> {
 
     /// Token configuration
     #[account(
-        mut,
         seeds = [CONFIG_SEED, mint.key().as_ref()],
         bump,
         has_one = owner @ ERC3643Error::OwnerRoleRequired
@@ -445,30 +509,43 @@ pub struct AddAgent<'info> {
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
 }
 
-pub fn add_agent(ctx: Context<AddAgent>, agent: Pubkey) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    let owner = &ctx.accounts.owner;
+pub fn add_agent(ctx: Context<AddAgent>, agent: Pubkey, role: Role) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    let owner = ctx.accounts.owner.key();
+    apply_add_agent(&mut ctx.accounts.role_registry, agent, role, owner)
+}
 
+pub(crate) fn apply_add_agent(roles: &mut RoleRegistry, agent: Pubkey, role: Role, by: Pubkey) -> Result<()> {
     // Validate input
     validate_pubkey(&agent)?;
+    require!(role.is_agent_role(), ERC3643Error::InvalidArgument);
 
-    // Add the agent
-    config.add_agent(agent)?;
+    // Grant the agent role
+    roles.grant(agent, role)?;
 
     emit!(AgentAdded {
         agent,
-        added_by: owner.key(),
+        role,
+        added_by: by,
     });
 
-    msg!("Agent added: {}", agent);
-    msg!("Added by owner: {}", owner.key());
+    msg!("Agent added: {} as {:?}", agent, role);
+    msg!("Added by owner: {}", by);
 
     Ok(())
 }
 
-/// Remove agent
+/// Remove an agent role from the role registry
 #[derive(Accounts)]
 pub struct RemoveAgent<'info> {
     /// Owner performing the operation
@@ -477,7 +554,6 @@ pub struct RemoveAgent<'info> {
 
     /// Token configuration
     #[account(
-        mut,
         seeds = [CONFIG_SEED, mint.key().as_ref()],
         bump,
         has_one = owner @ ERC3643Error::OwnerRoleRequired
@@ -486,30 +562,430 @@ pub struct RemoveAgent<'info> {
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
 }
 
-pub fn remove_agent(ctx: Context<RemoveAgent>, agent: Pubkey) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    let owner = &ctx.accounts.owner;
+pub fn remove_agent(ctx: Context<RemoveAgent>, agent: Pubkey, role: Role) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    let owner = ctx.accounts.owner.key();
+    apply_remove_agent(&mut ctx.accounts.role_registry, agent, role, owner)
+}
 
+pub(crate) fn apply_remove_agent(roles: &mut RoleRegistry, agent: Pubkey, role: Role, by: Pubkey) -> Result<()> {
     // Validate input
     validate_pubkey(&agent)?;
+    require!(role.is_agent_role(), ERC3643Error::InvalidArgument);
 
-    // Remove the agent
-    config.remove_agent(agent)?;
+    // Revoke the agent role
+    roles.revoke(agent, role)?;
 
     emit!(AgentRemoved {
         agent,
-        removed_by: owner.key(),
+        role,
+        removed_by: by,
+    });
+
+    msg!("Agent removed: {} as {:?}", agent, role);
+    msg!("Removed by owner: {}", by);
+
+    Ok(())
+}
+
+/// Set the compliance manager, moving the compliance manager role in the
+/// role registry from the previous manager
+#[derive(Accounts)]
+pub struct SetComplianceManager<'info> {
+    /// Owner performing the operation
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+}
+
+pub fn set_compliance_manager(
+    ctx: Context<SetComplianceManager>,
+    compliance_manager: Pubkey,
+) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_set_compliance_manager(&mut ctx.accounts.config, &mut ctx.accounts.role_registry, compliance_manager)
+}
+
+pub(crate) fn apply_set_compliance_manager(
+    config: &mut TokenConfig,
+    roles: &mut RoleRegistry,
+    compliance_manager: Pubkey,
+) -> Result<()> {
+    // Pubkey::default() clears the compliance manager
+    let previous_manager = config.compliance_manager;
+    let role = Role::ComplianceManager;
+    if previous_manager != Pubkey::default() && roles.roles_of(&previous_manager) & role.bit() != 0 {
+        roles.revoke(previous_manager, role)?;
+    }
+    if compliance_manager != Pubkey::default() && roles.roles_of(&compliance_manager) & role.bit() == 0 {
+        roles.grant(compliance_manager, role)?;
+    }
+
+    config.compliance_manager = compliance_manager;
+    config.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(ComplianceManagerSet {
+        previous_manager,
+        new_manager: compliance_manager,
+    });
+
+    msg!("Compliance manager set to: {}", compliance_manager);
+
+    Ok(())
+}
+
+/// Grant a role in the role registry
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    /// Owner performing the operation
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+}
+
+pub fn grant_role(ctx: Context<GrantRole>, member: Pubkey, role: Role) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    let mint = ctx.accounts.mint.key();
+    let owner = ctx.accounts.owner.key();
+    apply_grant_role(&mut ctx.accounts.role_registry, mint, member, role, owner)
+}
+
+pub(crate) fn apply_grant_role(
+    roles: &mut RoleRegistry,
+    mint: Pubkey,
+    member: Pubkey,
+    role: Role,
+    by: Pubkey,
+) -> Result<()> {
+    validate_pubkey(&member)?;
+
+    roles.grant(member, role)?;
+
+    emit!(RoleGranted {
+        mint,
+        member,
+        role,
+        granted_by: by,
+    });
+
+    msg!("Role {:?} granted to: {}", role, member);
+
+    Ok(())
+}
+
+/// Revoke a role from the role registry
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    /// Owner performing the operation
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+}
+
+pub fn revoke_role(ctx: Context<RevokeRole>, member: Pubkey, role: Role) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    let mint = ctx.accounts.mint.key();
+    let owner = ctx.accounts.owner.key();
+    apply_revoke_role(&mut ctx.accounts.role_registry, mint, member, role, owner)
+}
+
+pub(crate) fn apply_revoke_role(
+    roles: &mut RoleRegistry,
+    mint: Pubkey,
+    member: Pubkey,
+    role: Role,
+    by: Pubkey,
+) -> Result<()> {
+    roles.revoke(member, role)?;
+
+    emit!(RoleRevoked {
+        mint,
+        member,
+        role,
+        revoked_by: by,
     });
 
-    msg!("Agent removed: {}", agent);
-    msg!("Removed by owner: {}", owner.key());
+    msg!("Role {:?} revoked from: {}", role, member);
 
     Ok(())
 }
 
-/// Transfer ownership
+/// Update the token name, symbol and onchain ID
+#[derive(Accounts)]
+pub struct SetTokenInformation<'info> {
+    /// Owner performing the operation, funds any metadata growth
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint, holding the TokenMetadata extension
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Token program (Token-2022)
+    pub token_program: Program<'info, Token2022>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_token_information(
+    ctx: Context<SetTokenInformation>,
+    name: Option<String>,
+    symbol: Option<String>,
+    onchain_id: Option<Pubkey>,
+) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    let accounts = TokenMetadataAccounts {
+        mint: &ctx.accounts.mint,
+        token_program: ctx.accounts.token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        payer: ctx.accounts.owner.to_account_info(),
+    };
+    apply_set_token_information(&mut ctx.accounts.config, accounts, ctx.bumps.config, name, symbol, onchain_id)
+}
+
+/// Accounts needed to update the mint's TokenMetadata
+pub(crate) struct TokenMetadataAccounts<'a, 'info> {
+    pub mint: &'a InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// Funds any metadata growth
+    pub payer: AccountInfo<'info>,
+}
+
+pub(crate) fn apply_set_token_information<'info>(
+    config: &mut Account<'info, TokenConfig>,
+    accounts: TokenMetadataAccounts<'_, 'info>,
+    config_bump: u8,
+    name: Option<String>,
+    symbol: Option<String>,
+    onchain_id: Option<Pubkey>,
+) -> Result<()> {
+    config.update_metadata(name.clone(), symbol.clone())?;
+    if let Some(onchain_id) = onchain_id {
+        // Pubkey::default() clears the onchain ID
+        config.onchain_id = onchain_id;
+    }
+
+    // Mirror name, symbol and onchain ID into the mint's TokenMetadata, whose
+    // update authority is the config PDA
+    let mut updates: Vec<(Field, String)> = name
+        .map(|name| (Field::Name, name))
+        .into_iter()
+        .chain(symbol.map(|symbol| (Field::Symbol, symbol)))
+        .collect();
+    let mut clear_onchain_id = false;
+    if let Some(onchain_id) = onchain_id {
+        if onchain_id == Pubkey::default() {
+            clear_onchain_id = true;
+        } else {
+            updates.push((Field::Key(ONCHAIN_ID_METADATA_KEY.into()), onchain_id.to_string()));
+        }
+    }
+
+    let mint_info = accounts.mint.to_account_info();
+    let mint_key = accounts.mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[config_bump]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    // Token-2022 reallocates the mint but does not fund the growth
+    let (data_len, mut metadata) = {
+        let data = mint_info.try_borrow_data()?;
+        let state = StateWithExtensions::<MintState>::unpack(&data)?;
+        (data.len(), state.get_variable_len_extension::<TokenMetadata>()?)
+    };
+    clear_onchain_id = clear_onchain_id
+        && metadata
+            .additional_metadata
+            .iter()
+            .any(|(key, _)| key == ONCHAIN_ID_METADATA_KEY);
+
+    if !updates.is_empty() {
+        let old_size = metadata.tlv_size_of()?;
+        for (field, value) in &updates {
+            metadata.update(field.clone(), value.clone());
+        }
+        let new_len = data_len - old_size + metadata.tlv_size_of()?;
+        let required = Rent::get()?.minimum_balance(new_len);
+        let current = mint_info.lamports();
+        if required > current {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    accounts.system_program.clone(),
+                    anchor_lang::system_program::Transfer {
+                        from: accounts.payer.clone(),
+                        to: mint_info.clone(),
+                    },
+                ),
+                required - current,
+            )?;
+        }
+
+        for (field, value) in updates {
+            token_metadata_update_field(
+                CpiContext::new_with_signer(
+                    accounts.token_program.clone(),
+                    TokenMetadataUpdateField {
+                        token_program_id: accounts.token_program.clone(),
+                        metadata: mint_info.clone(),
+                        update_authority: config.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                field,
+                value,
+            )?;
+        }
+    }
+
+    if clear_onchain_id {
+        let ix = spl_token_metadata_interface::instruction::remove_key(
+            &accounts.token_program.key(),
+            &mint_key,
+            &config.key(),
+            ONCHAIN_ID_METADATA_KEY.into(),
+            true,
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[mint_info, config.to_account_info()],
+            signer_seeds,
+        )?;
+    }
+
+    emit!(UpdatedTokenInformation {
+        name: config.name.clone(),
+        symbol: config.symbol.clone(),
+        decimals: accounts.mint.decimals,
+        version: TOKEN_VERSION.to_string(),
+        onchain_id: config.onchain_id,
+    });
+
+    msg!("Token information updated: {} ({})", config.name, config.symbol);
+    msg!("Onchain ID: {}", config.onchain_id);
+
+    Ok(())
+}
+
+/// Set the supply cap
+#[derive(Accounts)]
+pub struct SetMaxSupply<'info> {
+    /// Owner performing the operation
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+}
+
+pub fn set_max_supply(ctx: Context<SetMaxSupply>, max_supply: Option<u64>) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    let mint = &ctx.accounts.mint;
+    apply_set_max_supply(&mut ctx.accounts.config, mint.key(), mint.supply, max_supply)
+}
+
+pub(crate) fn apply_set_max_supply(
+    config: &mut TokenConfig,
+    mint: Pubkey,
+    supply: u64,
+    max_supply: Option<u64>,
+) -> Result<()> {
+    config.set_max_supply(max_supply, supply)?;
+
+    emit!(MaxSupplyUpdated {
+        mint,
+        max_supply,
+    });
+
+    msg!("Max supply set to: {:?}", max_supply);
+
+    Ok(())
+}
+
+/// Nominate a new owner
 #[derive(Accounts)]
 pub struct TransferOwnership<'info> {
     /// Current owner
@@ -530,15 +1006,57 @@ pub struct TransferOwnership<'info> {
 }
 
 pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    let current_owner = &ctx.accounts.owner;
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_transfer_ownership(&mut ctx.accounts.config, new_owner)
+}
 
+pub(crate) fn apply_transfer_ownership(config: &mut TokenConfig, new_owner: Pubkey) -> Result<()> {
     // Validate input
     validate_pubkey(&new_owner)?;
-    require!(new_owner != current_owner.key(), ERC3643Error::InvalidArgument);
+    require!(new_owner != config.owner, ERC3643Error::InvalidArgument);
+
+    // The new owner takes over only once they accept
+    config.pending_owner = new_owner;
+    config.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(OwnershipTransferStarted {
+        owner: config.owner,
+        pending_owner: new_owner,
+    });
+
+    msg!("Ownership transfer to {} pending acceptance", new_owner);
+
+    Ok(())
+}
+
+/// Accept a pending ownership transfer
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    /// Pending owner accepting the transfer
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        constraint = config.pending_owner != Pubkey::default()
+            && config.pending_owner == new_owner.key() @ ERC3643Error::Unauthorized
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+}
+
+pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let new_owner = ctx.accounts.new_owner.key();
 
     let previous_owner = config.owner;
     config.owner = new_owner;
+    config.pending_owner = Pubkey::default();
     config.updated_at = Clock::get()?.unix_timestamp;
 
     emit!(OwnershipTransferred {
@@ -550,3 +1068,45 @@ pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) ->
 
     Ok(())
 }
+
+/// Cancel a pending ownership transfer
+#[derive(Accounts)]
+pub struct CancelOwnershipTransfer<'info> {
+    /// Current owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+}
+
+pub fn cancel_ownership_transfer(ctx: Context<CancelOwnershipTransfer>) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_cancel_ownership_transfer(&mut ctx.accounts.config)
+}
+
+pub(crate) fn apply_cancel_ownership_transfer(config: &mut TokenConfig) -> Result<()> {
+    let pending_owner = config.pending_owner;
+    require!(pending_owner != Pubkey::default(), ERC3643Error::InvalidArgument);
+
+    config.pending_owner = Pubkey::default();
+    config.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(OwnershipTransferCancelled {
+        owner: config.owner,
+        pending_owner,
+    });
+
+    msg!("Ownership transfer to {} cancelled", pending_owner);
+
+    Ok(())
+}
@@ -0,0 +1,626 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::Token2022,
+    token_interface::{burn, mint_to, thaw_account, Burn, Mint, MintTo, ThawAccount},
+};
+use crate::{
+    error::ERC3643Error,
+    events::*,
+    modules::{check_modules, notify_modules, ModuleCall, ModuleHook},
+    state::*,
+    utils::{
+        create_pda_if_needed, read_optional_account, read_program_account, read_token_account,
+        validate_batch_size, write_program_account,
+    },
+};
+
+/// Register identities for many users
+///
+/// Remaining accounts come in groups of three per user: identity storage,
+/// identity registry and transfer counter PDAs. Users already registered for
+/// this token are skipped.
+#[derive(Accounts)]
+pub struct BatchRegisterIdentity<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Agent or owner performing the registration
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint the identities are for
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::IdentityAgent) @ ERC3643Error::IdentityAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn batch_register_identity<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchRegisterIdentity<'info>>,
+    users: Vec<Pubkey>,
+    countries: Vec<u16>,
+) -> Result<BatchResult> {
+    require!(users.len() == countries.len(), ERC3643Error::InvalidArgument);
+    validate_batch_size(users.len(), MAX_BATCH_SIZE)?;
+    require!(
+        ctx.remaining_accounts.len() == users.len() * 3,
+        ERC3643Error::InvalidArgument
+    );
+
+    let payer = ctx.accounts.payer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let mint_key = ctx.accounts.mint.key();
+    let agent = ctx.accounts.agent.key();
+    let now = Clock::get()?.unix_timestamp;
+    let mut result = BatchResult::new(users.len());
+
+    for (index, (accounts, (user, country))) in ctx
+        .remaining_accounts
+        .chunks(3)
+        .zip(users.iter().zip(countries))
+        .enumerate()
+    {
+        let (storage_info, registry_info, counter_info) = (&accounts[0], &accounts[1], &accounts[2]);
+
+        if validate_pubkey(user).is_err() || validate_country_code(country).is_err() {
+            result.record_failure(index, "invalid user or country");
+            continue;
+        }
+
+        if !create_pda_if_needed(
+            registry_info,
+            &payer,
+            &system_program,
+            IdentityRegistry::LEN,
+            &[IDENTITY_SEED, mint_key.as_ref(), user.as_ref()],
+        )? {
+            result.record_failure(index, "already registered");
+            continue;
+        }
+
+        // Initialize the shared storage on first registration
+        let created = create_pda_if_needed(
+            storage_info,
+            &payer,
+            &system_program,
+            IdentityStorage::LEN,
+            &[IDENTITY_STORAGE_SEED, user.as_ref()],
+        )?;
+        let mut storage: IdentityStorage = read_program_account(storage_info, created)?;
+        if storage.user == Pubkey::default() {
+            storage.user = *user;
+            storage.identity = *user;
+            storage.country = country;
+            storage.onchain_id = None;
+            storage.bound_registries = 0;
+            storage.created_at = now;
+            storage.updated_at = now;
+            storage.created_by = agent;
+            storage.reserved = [0; 32];
+        }
+
+        let mut identity: IdentityRegistry = read_program_account(registry_info, true)?;
+        identity.user = *user;
+        identity.is_verified = false; // Will be set to true once required claims are added
+        identity.country = country;
+        identity.onchain_id = storage.onchain_id;
+        identity.claims_count = 0;
+        identity.required_claims_met = false;
+        identity.registered_at = now;
+        identity.last_verified_at = 0;
+        identity.updated_at = now;
+        identity.registered_by = agent;
+        identity.identity = storage.identity;
+        identity.mint = mint_key;
+        identity.storage = storage_info.key();
+        identity.reserved = [0; 32];
+
+        storage.bind()?;
+
+        let created = create_pda_if_needed(
+            counter_info,
+            &payer,
+            &system_program,
+            TransferCounter::LEN,
+            &[TRANSFER_COUNTER_SEED, mint_key.as_ref(), user.as_ref()],
+        )?;
+        let mut counter: TransferCounter = read_program_account(counter_info, created)?;
+        counter.initialize_if_new(mint_key, *user);
+
+        write_program_account(storage_info, &storage)?;
+        write_program_account(registry_info, &identity)?;
+        write_program_account(counter_info, &counter)?;
+
+        emit!(IdentityRegistryBound {
+            identity_storage: storage_info.key(),
+            identity_registry: registry_info.key(),
+            mint: mint_key,
+        });
+
+        emit!(IdentityRegistered {
+            investor_address: *user,
+            identity: registry_info.key(),
+        });
+
+        result.success += 1;
+    }
+
+    emit_batch_completed("register_identity", &result);
+
+    Ok(result)
+}
+
+/// Set the frozen status of many addresses
+///
+/// Remaining accounts hold one frozen account PDA per user, created when
+/// missing.
+#[derive(Accounts)]
+pub struct BatchSetAddressFrozen<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Agent performing the operation
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::FreezeAgent) @ ERC3643Error::FreezeAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn batch_set_address_frozen<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchSetAddressFrozen<'info>>,
+    users: Vec<Pubkey>,
+    frozen: Vec<bool>,
+) -> Result<BatchResult> {
+    require!(users.len() == frozen.len(), ERC3643Error::InvalidArgument);
+    validate_batch_size(users.len(), MAX_BATCH_SIZE)?;
+    require!(
+        ctx.remaining_accounts.len() == users.len(),
+        ERC3643Error::InvalidArgument
+    );
+
+    let payer = ctx.accounts.payer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let mint_key = ctx.accounts.mint.key();
+    let agent = ctx.accounts.agent.key();
+    let mut result = BatchResult::new(users.len());
+
+    for (index, (frozen_info, (user, is_frozen))) in ctx
+        .remaining_accounts
+        .iter()
+        .zip(users.iter().zip(frozen))
+        .enumerate()
+    {
+        if validate_pubkey(user).is_err() {
+            result.record_failure(index, "invalid user");
+            continue;
+        }
+
+        let created = create_pda_if_needed(
+            frozen_info,
+            &payer,
+            &system_program,
+            FrozenAccount::LEN,
+            &[b"frozen", mint_key.as_ref(), user.as_ref()],
+        )?;
+        let mut frozen_account: FrozenAccount = read_program_account(frozen_info, created)?;
+        if frozen_account.user == Pubkey::default() {
+            frozen_account.user = *user;
+            frozen_account.mint = mint_key;
+            frozen_account.is_frozen = false;
+            frozen_account.frozen_amount = 0;
+            frozen_account.frozen_at = 0;
+            frozen_account.frozen_by = Pubkey::default();
+            frozen_account.reserved = [0; 64];
+        }
+
+        frozen_account.set_frozen(is_frozen, agent)?;
+        write_program_account(frozen_info, &frozen_account)?;
+
+        emit!(AddressFrozen {
+            user_address: *user,
+            is_frozen,
+            agent,
+        });
+
+        result.success += 1;
+    }
+
+    emit_batch_completed("set_address_frozen", &result);
+
+    Ok(result)
+}
+
+/// Mint tokens to many verified addresses
+///
+/// Remaining accounts come in groups of two per recipient: token account and
+/// identity registry PDA, followed by the accounts of the custom compliance
+/// modules. Recipients failing verification or compliance are skipped.
+#[derive(Accounts)]
+pub struct BatchMint<'info> {
+    /// Agent or owner performing the mint
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint account
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::SupplyAgent) @ ERC3643Error::SupplyAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
+    /// Token program
+    pub token_program: Program<'info, Token2022>,
+}
+
+pub fn batch_mint<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchMint<'info>>,
+    amounts: Vec<u64>,
+) -> Result<BatchResult> {
+    validate_batch_size(amounts.len(), MAX_BATCH_SIZE)?;
+    require!(!ctx.accounts.config.paused, ERC3643Error::TokenPaused);
+
+    let group_accounts = amounts.len() * 2;
+    require!(
+        ctx.remaining_accounts.len() >= group_accounts,
+        ERC3643Error::InvalidArgument
+    );
+    let (groups, module_accounts) = ctx.remaining_accounts.split_at(group_accounts);
+
+    let config_info = ctx.accounts.config.to_account_info();
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let mint_key = ctx.accounts.mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    let max_supply = ctx.accounts.config.max_supply;
+    let compliance = &mut ctx.accounts.compliance_registry;
+    let modules = compliance.custom_modules();
+    let mut supply = ctx.accounts.mint.supply;
+    let mut minted: u64 = 0;
+    let mut result = BatchResult::new(amounts.len());
+
+    for (index, (accounts, amount)) in groups.chunks(2).zip(amounts).enumerate() {
+        let (to_info, identity_info) = (&accounts[0], &accounts[1]);
+
+        if amount == 0 {
+            result.record_failure(index, "invalid amount");
+            continue;
+        }
+
+        let to_account = read_token_account(to_info, &mint_key, token_program.key)?;
+        let identity: Option<IdentityRegistry> = read_optional_account(
+            identity_info,
+            &[IDENTITY_SEED, mint_key.as_ref(), to_account.owner.as_ref()],
+        )?;
+        let Some(identity) = identity.filter(|identity| identity.is_verified) else {
+            result.record_failure(index, "identity not verified");
+            continue;
+        };
+
+        let new_supply = supply
+            .checked_add(amount)
+            .ok_or(ERC3643Error::ArithmeticOverflow)?;
+        if max_supply.is_some_and(|max| new_supply > max) {
+            result.record_failure(index, "max supply exceeded");
+            continue;
+        }
+
+        let compliance_result = compliance.check_transfer_compliance(
+            &TransferCheck {
+                from: Pubkey::default(),
+                to: to_account.owner,
+                amount,
+                from_balance: 0,
+                to_balance: to_account.amount,
+                supply: new_supply,
+                from_country: 0,
+                to_country: identity.country,
+            },
+            None,
+        )?;
+        if !compliance_result.allowed || compliance_result.requires_approval {
+            result.record_failure(index, "not compliant");
+            continue;
+        }
+
+        if check_modules(
+            &modules,
+            module_accounts,
+            &ModuleCall {
+                config: &config_info,
+                mint: &mint_info,
+                from: Pubkey::default(),
+                to: to_account.owner,
+                amount,
+            },
+        )?
+        .is_some()
+        {
+            result.record_failure(index, "rejected by module");
+            continue;
+        }
+
+        // New token accounts start frozen; the verified recipient can be thawed
+        if to_account.is_frozen() {
+            thaw_account(CpiContext::new_with_signer(
+                token_program.clone(),
+                ThawAccount {
+                    account: to_info.clone(),
+                    mint: mint_info.clone(),
+                    authority: config_info.clone(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        mint_to(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                MintTo {
+                    mint: mint_info.clone(),
+                    to: to_info.clone(),
+                    authority: config_info.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        compliance.record_balance_change(
+            identity.country,
+            to_account.amount,
+            to_account.amount.saturating_add(amount),
+        )?;
+
+        notify_modules(
+            ModuleHook::Mint,
+            &modules,
+            module_accounts,
+            &ModuleCall {
+                config: &config_info,
+                mint: &mint_info,
+                from: Pubkey::default(),
+                to: to_account.owner,
+                amount,
+            },
+        )?;
+
+        supply = new_supply;
+        minted += amount;
+        result.success += 1;
+    }
+
+    let config = &mut ctx.accounts.config;
+    let current_supply = config.total_supply;
+    config.update_total_supply(current_supply + minted)?;
+
+    emit_batch_completed("mint", &result);
+
+    msg!("Batch minted {} tokens", minted);
+    msg!("New total supply: {}", config.total_supply);
+
+    Ok(result)
+}
+
+/// Burn tokens from many addresses
+///
+/// Remaining accounts come in groups of three per holder: token account,
+/// identity registry PDA and frozen account PDA (either PDA may not exist),
+/// followed by the accounts of the custom compliance modules. The config PDA
+/// burns as permanent delegate, releasing frozen tokens when needed.
+#[derive(Accounts)]
+pub struct BatchBurn<'info> {
+    /// Agent or owner performing the burn
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint account
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::SupplyAgent) @ ERC3643Error::SupplyAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
+    /// Token program
+    pub token_program: Program<'info, Token2022>,
+}
+
+pub fn batch_burn<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchBurn<'info>>,
+    amounts: Vec<u64>,
+) -> Result<BatchResult> {
+    validate_batch_size(amounts.len(), MAX_BATCH_SIZE)?;
+    require!(!ctx.accounts.config.paused, ERC3643Error::TokenPaused);
+
+    let group_accounts = amounts.len() * 3;
+    require!(
+        ctx.remaining_accounts.len() >= group_accounts,
+        ERC3643Error::InvalidArgument
+    );
+    let (groups, module_accounts) = ctx.remaining_accounts.split_at(group_accounts);
+
+    let config_info = ctx.accounts.config.to_account_info();
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let mint_key = ctx.accounts.mint.key();
+    let agent = ctx.accounts.agent.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    let compliance = &mut ctx.accounts.compliance_registry;
+    let modules = compliance.custom_modules();
+    let mut burned: u64 = 0;
+    let mut result = BatchResult::new(amounts.len());
+
+    for (index, (accounts, amount)) in groups.chunks(3).zip(amounts).enumerate() {
+        let (from_info, identity_info, frozen_info) = (&accounts[0], &accounts[1], &accounts[2]);
+
+        if amount == 0 {
+            result.record_failure(index, "invalid amount");
+            continue;
+        }
+
+        let from_account = read_token_account(from_info, &mint_key, token_program.key)?;
+        if from_account.amount < amount {
+            result.record_failure(index, "insufficient balance");
+            continue;
+        }
+
+        let owner = from_account.owner;
+        let identity: Option<IdentityRegistry> = read_optional_account(
+            identity_info,
+            &[IDENTITY_SEED, mint_key.as_ref(), owner.as_ref()],
+        )?;
+        let frozen: Option<FrozenAccount> = read_optional_account(
+            frozen_info,
+            &[b"frozen", mint_key.as_ref(), owner.as_ref()],
+        )?;
+
+        // Release frozen tokens if the burn needs them
+        if let Some(mut frozen_account) = frozen {
+            let free_balance = from_account.amount.saturating_sub(frozen_account.frozen_amount);
+            if amount > free_balance {
+                let tokens_to_unfreeze = amount - free_balance;
+                frozen_account.unfreeze_tokens(tokens_to_unfreeze, agent)?;
+                write_program_account(frozen_info, &frozen_account)?;
+
+                emit!(TokensUnfrozen {
+                    user_address: owner,
+                    amount: tokens_to_unfreeze,
+                });
+            }
+        }
+
+        burn(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                Burn {
+                    mint: mint_info.clone(),
+                    from: from_info.clone(),
+                    authority: config_info.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        compliance.record_balance_change(
+            identity.map_or(0, |identity| identity.country),
+            from_account.amount,
+            from_account.amount - amount,
+        )?;
+
+        notify_modules(
+            ModuleHook::Burn,
+            &modules,
+            module_accounts,
+            &ModuleCall {
+                config: &config_info,
+                mint: &mint_info,
+                from: owner,
+                to: Pubkey::default(),
+                amount,
+            },
+        )?;
+
+        burned += amount;
+        result.success += 1;
+    }
+
+    let config = &mut ctx.accounts.config;
+    let current_supply = config.total_supply;
+    config.update_total_supply(current_supply.saturating_sub(burned))?;
+
+    emit_batch_completed("burn", &result);
+
+    msg!("Batch burned {} tokens", burned);
+    msg!("New total supply: {}", config.total_supply);
+
+    Ok(result)
+}
+
+fn emit_batch_completed(operation_type: &str, result: &BatchResult) {
+    emit!(BatchOperationCompleted {
+        operation_type: operation_type.to_string(),
+        count: result.total,
+        success_count: result.success,
+        failure_count: result.failures.len() as u32,
+    });
+}
@@ -1,12 +1,23 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
 use crate::{
     error::ERC3643Error,
     events::*,
+    modules::{check_modules, notify_modules, ModuleCall, ModuleHook},
     state::*,
+    utils::{
+        is_transferring, load_identity_claims, safe_token_math,
+        verify_required_claims_coverage, TokenMathOperation,
+    },
 };
 
-/// Check if transfer is allowed (read-only)
+/// Check if transfer is allowed
+///
+/// Called directly to simulate a transfer, or by the transfer hook while
+/// Token-2022 is executing one. Accounts of the custom compliance modules
+/// must be passed as remaining accounts.
 #[derive(Accounts)]
+#[instruction(from: Pubkey, to: Pubkey)]
 pub struct CheckTransferAllowed<'info> {
     /// Token configuration
     #[account(
@@ -16,25 +27,40 @@ pub struct CheckTransferAllowed<'info> {
     pub config: Account<'info, TokenConfig>,
 
     /// The mint account
-    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Source token account (optional for minting)
+    #[account(
+        constraint = from_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument,
+        constraint = from_token_account.owner == from @ ERC3643Error::InvalidArgument
+    )]
+    pub from_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Destination token account
+    #[account(
+        constraint = to_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument,
+        constraint = to_token_account.owner == to @ ERC3643Error::InvalidArgument
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// From identity registry (optional for minting)
     #[account(
-        seeds = [IDENTITY_SEED, from_identity.key().as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), from.as_ref()],
         bump
     )]
     pub from_identity_registry: Option<Account<'info, IdentityRegistry>>,
 
     /// To identity registry
     #[account(
-        seeds = [IDENTITY_SEED, to_identity.key().as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), to.as_ref()],
         bump,
         constraint = to_identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
     )]
     pub to_identity_registry: Account<'info, IdentityRegistry>,
 
-    /// Compliance registry
+    /// Compliance registry, whose investor count moves with the transfer
     #[account(
+        mut,
         seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
         bump
     )]
@@ -47,31 +73,31 @@ pub struct CheckTransferAllowed<'info> {
     )]
     pub claim_topics_registry: Account<'info, ClaimTopicsRegistry>,
 
-    /// From identity (for PDA derivation)
-    /// CHECK: Used for PDA derivation only
-    pub from_identity: UncheckedAccount<'info>,
-
-    /// To identity (for PDA derivation)
-    /// CHECK: Used for PDA derivation only
-    pub to_identity: UncheckedAccount<'info>,
-
     /// From frozen account (if exists)
     #[account(
-        seeds = [b"frozen", mint.key().as_ref(), from_identity.key().as_ref()],
+        seeds = [b"frozen", mint.key().as_ref(), from.as_ref()],
         bump
     )]
     pub from_frozen_account: Option<Account<'info, FrozenAccount>>,
 
     /// To frozen account (if exists)
     #[account(
-        seeds = [b"frozen", mint.key().as_ref(), to_identity.key().as_ref()],
+        seeds = [b"frozen", mint.key().as_ref(), to.as_ref()],
         bump
     )]
     pub to_frozen_account: Option<Account<'info, FrozenAccount>>,
+
+    /// Sender's transfer volume counter, updated while a transfer executes
+    #[account(
+        mut,
+        seeds = [TRANSFER_COUNTER_SEED, mint.key().as_ref(), from.as_ref()],
+        bump
+    )]
+    pub from_transfer_counter: Option<Account<'info, TransferCounter>>,
 }
 
-pub fn check_transfer_allowed(
-    ctx: Context<CheckTransferAllowed>,
+pub fn check_transfer_allowed<'info>(
+    ctx: Context<'_, '_, '_, 'info, CheckTransferAllowed<'info>>,
     from: Pubkey,
     to: Pubkey,
     amount: u64,
@@ -82,6 +108,8 @@ pub fn check_transfer_allowed(
     let from_identity = ctx.accounts.from_identity_registry.as_ref();
     let from_frozen = ctx.accounts.from_frozen_account.as_ref();
     let to_frozen = ctx.accounts.to_frozen_account.as_ref();
+    let from_token_account = ctx.accounts.from_token_account.as_ref();
+    let to_token_account = &ctx.accounts.to_token_account;
 
     // Check if token is paused
     if config.paused {
@@ -122,6 +150,34 @@ pub fn check_transfer_allowed(
         }
     }
 
+    // Fetch balances from the token accounts. Inside the transfer hook the
+    // amount has already moved, so rebuild the pre-transfer balances
+    let in_transfer = is_transferring(&to_token_account.to_account_info())?;
+    let mut from_balance = from_token_account.map_or(0, |account| account.amount);
+    let mut to_balance = to_token_account.amount;
+    if in_transfer {
+        from_balance = safe_token_math(TokenMathOperation::Add, from_balance, amount)?;
+        to_balance = to_balance.saturating_sub(amount);
+    }
+
+    // Partially frozen tokens cannot be spent
+    let frozen_amount = from_frozen.map_or(0, |info| info.get_frozen_amount());
+    let spendable_balance = from_balance.saturating_sub(frozen_amount);
+
+    if amount > spendable_balance && frozen_amount > 0 {
+        emit!(TransferValidated {
+            from,
+            to,
+            amount,
+            allowed: false,
+            reason: format!(
+                "Amount exceeds unfrozen balance ({} of {} tokens frozen)",
+                frozen_amount, from_balance
+            ),
+        });
+        return Ok(false);
+    }
+
     // Check identity verification for recipient
     if !to_identity.is_verified {
         emit!(TransferValidated {
@@ -167,21 +223,66 @@ pub fn check_transfer_allowed(
     };
     let to_country = to_identity.country;
 
-    // Simulate token balances (in real implementation, these would be fetched from token accounts)
-    let from_balance = 1000; // Placeholder
-    let to_balance = 500;    // Placeholder
-
     // Check compliance
-    let compliance_result = compliance.check_transfer_compliance(
-        &from,
-        &to,
-        amount,
-        from_balance,
-        to_balance,
-        from_country,
-        to_country,
+    let mut compliance_result = compliance.check_transfer_compliance(
+        &TransferCheck {
+            from,
+            to,
+            amount,
+            from_balance: spendable_balance,
+            to_balance,
+            supply: ctx.accounts.mint.supply,
+            from_country,
+            to_country,
+        },
+        ctx.accounts.from_transfer_counter.as_deref(),
     )?;
 
+    // External modules get the final say
+    let modules = compliance.custom_modules();
+    if compliance_result.allowed && !modules.is_empty() {
+        if let Some(module) = check_modules(
+            &modules,
+            ctx.remaining_accounts,
+            &ModuleCall {
+                config: &config.to_account_info(),
+                mint: &ctx.accounts.mint.to_account_info(),
+                from,
+                to,
+                amount,
+            },
+        )? {
+            compliance_result.allowed = false;
+            compliance_result.reason = format!("Rejected by compliance module {}", module);
+            compliance_result.module = module;
+        }
+    }
+
+    // Count the volume and investors once the transfer is actually going through
+    if compliance_result.allowed && in_transfer {
+        notify_modules(
+            ModuleHook::Transfer,
+            &modules,
+            ctx.remaining_accounts,
+            &ModuleCall {
+                config: &ctx.accounts.config.to_account_info(),
+                mint: &ctx.accounts.mint.to_account_info(),
+                from,
+                to,
+                amount,
+            },
+        )?;
+
+        let compliance = &mut ctx.accounts.compliance_registry;
+        if let Some(counter) = ctx.accounts.from_transfer_counter.as_mut() {
+            let now = Clock::get()?.unix_timestamp;
+            counter.record_transfer(&compliance.transfer_limits, amount, now)?;
+        }
+
+        compliance.record_balance_change(from_country, from_balance, from_balance.saturating_sub(amount))?;
+        compliance.record_balance_change(to_country, to_balance, to_balance.saturating_add(amount))?;
+    }
+
     emit!(TransferValidated {
         from,
         to,
@@ -216,7 +317,7 @@ pub struct CheckVerification<'info> {
 
     /// Identity registry
     #[account(
-        seeds = [IDENTITY_SEED, user.key().as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), user.key().as_ref()],
         bump
     )]
     pub identity_registry: Account<'info, IdentityRegistry>,
@@ -329,6 +430,10 @@ pub fn validate_claim(ctx: Context<ValidateClaim>) -> Result<ClaimVerificationRe
 }
 
 /// Perform comprehensive identity verification
+///
+/// All claim accounts of the identity must be passed as remaining accounts.
+/// Anyone may call this, so it only ever raises verification; lowering is
+/// left to claim revocation and removal.
 #[derive(Accounts)]
 pub struct VerifyIdentity<'info> {
     /// Token configuration
@@ -344,7 +449,7 @@ pub struct VerifyIdentity<'info> {
     /// Identity registry to verify
     #[account(
         mut,
-        seeds = [IDENTITY_SEED, identity_registry.user.as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
         bump
     )]
     pub identity_registry: Account<'info, IdentityRegistry>,
@@ -369,37 +474,21 @@ pub fn verify_identity(ctx: Context<VerifyIdentity>) -> Result<IdentityVerificat
     let claim_topics = &ctx.accounts.claim_topics_registry;
     let trusted_issuers = &ctx.accounts.trusted_issuers_registry;
 
-    let mut result = IdentityVerificationResult::new(identity.user);
-    let required_topics = claim_topics.get_required_topics();
-
-    // In a full implementation, we would:
-    // 1. Fetch all claims for this identity
-    // 2. Validate each claim
-    // 3. Check coverage of required topics
-    // 4. Update verification status
-
-    // For now, we'll simulate the verification process
-    result.claims_checked = identity.claims_count;
-    
-    // Simulate checking required topics
-    for topic in required_topics {
-        // Check if we have valid claims for this topic
-        let has_valid_claim = true; // Placeholder - would check actual claims
-        
-        if has_valid_claim {
-            result.add_valid_claim(*topic);
-        } else {
-            result.add_missing_topic(*topic);
-        }
+    // Check every claim of the identity against the required topics
+    let claims = load_identity_claims(ctx.remaining_accounts, &identity.identity)?;
+    let result = verify_required_claims_coverage(
+        &identity.identity,
+        &claims,
+        claim_topics.get_required_topics(),
+        trusted_issuers,
+    );
+
+    // Update identity registry, never lowering verification
+    if result.is_verified {
+        identity.update_verification(true)?;
+        identity.update_required_claims_status(true)?;
     }
 
-    // Finalize verification result
-    result.finalize(required_topics);
-
-    // Update identity registry
-    identity.update_verification(result.is_verified)?;
-    identity.update_required_claims_status(result.is_verified)?;
-
     msg!("Identity verification completed for: {}", identity.user);
     msg!("Verified: {}", result.is_verified);
     msg!("Claims checked: {}", result.claims_checked);
@@ -0,0 +1,830 @@
+use anchor_lang::prelude::*;
+use crate::{
+    error::ERC3643Error,
+    events::*,
+    state::*,
+};
+
+/// Add a lock period
+#[derive(Accounts)]
+pub struct AddLockPeriod<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn add_lock_period(ctx: Context<AddLockPeriod>, lock_period: LockPeriod) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::AddLockPeriod { lock_period },
+    )
+}
+
+/// Update an existing lock period
+#[derive(Accounts)]
+pub struct UpdateLockPeriod<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn update_lock_period(
+    ctx: Context<UpdateLockPeriod>,
+    index: u8,
+    lock_period: LockPeriod,
+) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::UpdateLockPeriod { index, lock_period },
+    )
+}
+
+/// Remove a lock period
+#[derive(Accounts)]
+pub struct RemoveLockPeriod<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn remove_lock_period(ctx: Context<RemoveLockPeriod>, index: u8) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::RemoveLockPeriod { index },
+    )
+}
+
+/// Add a country restriction
+#[derive(Accounts)]
+pub struct AddCountryRestriction<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn add_country_restriction(
+    ctx: Context<AddCountryRestriction>,
+    restriction: CountryRestriction,
+) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::AddCountryRestriction { restriction },
+    )
+}
+
+/// Remove a country restriction
+#[derive(Accounts)]
+pub struct RemoveCountryRestriction<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn remove_country_restriction(
+    ctx: Context<RemoveCountryRestriction>,
+    country_code: u16,
+) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::RemoveCountryRestriction { country_code },
+    )
+}
+
+/// Set transfer limits
+#[derive(Accounts)]
+pub struct SetTransferLimits<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn set_transfer_limits(ctx: Context<SetTransferLimits>, limits: TransferLimits) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::SetTransferLimits { limits },
+    )
+}
+
+/// Set holding limits
+#[derive(Accounts)]
+pub struct SetHoldingLimits<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn set_holding_limits(ctx: Context<SetHoldingLimits>, limits: HoldingLimits) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::SetHoldingLimits { limits },
+    )
+}
+
+/// Add a trading window
+#[derive(Accounts)]
+pub struct AddTradingWindow<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn add_trading_window(ctx: Context<AddTradingWindow>, window: TradingWindow) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::AddTradingWindow { window },
+    )
+}
+
+/// Remove a trading window
+#[derive(Accounts)]
+pub struct RemoveTradingWindow<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn remove_trading_window(ctx: Context<RemoveTradingWindow>, index: u8) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::RemoveTradingWindow { index },
+    )
+}
+
+/// Set the investor cap
+#[derive(Accounts)]
+pub struct SetMaxInvestors<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn set_max_investors(ctx: Context<SetMaxInvestors>, max_investors: Option<u32>) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::SetMaxInvestors { max_investors },
+    )
+}
+
+/// Set the lifetime of transfer requests
+#[derive(Accounts)]
+pub struct SetTransferRequestTtl<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn set_transfer_request_ttl(ctx: Context<SetTransferRequestTtl>, ttl: i64) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::SetTransferRequestTtl { ttl },
+    )
+}
+
+/// Set or remove the investor cap for a country
+#[derive(Accounts)]
+pub struct SetCountryInvestorLimit<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn set_country_investor_limit(
+    ctx: Context<SetCountryInvestorLimit>,
+    country_code: u16,
+    max_investors: Option<u32>,
+    current_investors: u32,
+) -> Result<()> {
+    let compliance = &mut ctx.accounts.compliance_registry;
+    require!(!compliance.has_timelock(), ERC3643Error::TimelockRequired);
+    apply_compliance_action(
+        compliance,
+        ComplianceAction::SetCountryInvestorLimit {
+            country_code,
+            max_investors,
+        },
+        current_investors,
+    )
+}
+
+/// Add a compliance module, binding an external program for `Custom` modules
+#[derive(Accounts)]
+pub struct AddComplianceModule<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn add_compliance_module(
+    ctx: Context<AddComplianceModule>,
+    name: String,
+    module_type: ComplianceModuleType,
+    program_id: Pubkey,
+    account_count: u8,
+    parameters: Vec<u8>,
+) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::AddComplianceModule {
+            name,
+            module_type,
+            program_id,
+            account_count,
+            parameters,
+        },
+    )
+}
+
+/// Remove a compliance module
+#[derive(Accounts)]
+pub struct RemoveComplianceModule<'info> {
+    /// Owner or compliance manager performing the operation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn remove_compliance_module(ctx: Context<RemoveComplianceModule>, module_id: u32) -> Result<()> {
+    apply_now(
+        &mut ctx.accounts.compliance_registry,
+        ComplianceAction::RemoveComplianceModule { module_id },
+    )
+}
+
+/// Apply a compliance change right away, which the timelock forbids
+fn apply_now(compliance: &mut ComplianceRegistry, action: ComplianceAction) -> Result<()> {
+    require!(!compliance.has_timelock(), ERC3643Error::TimelockRequired);
+    apply_compliance_action(compliance, action, 0)
+}
+
+/// Apply a compliance change to the registry
+///
+/// `current_investors` is supplied when the change is applied rather than
+/// queued, and only seeds the count of a newly capped country.
+pub(crate) fn apply_compliance_action(
+    compliance: &mut ComplianceRegistry,
+    action: ComplianceAction,
+    current_investors: u32,
+) -> Result<()> {
+    match action {
+        ComplianceAction::AddLockPeriod { lock_period } => {
+            compliance.add_lock_period(lock_period.clone())?;
+
+            emit!(LockPeriodAdded {
+                mint: compliance.mint,
+                index: (compliance.lock_periods.len() - 1) as u8,
+                user: lock_period.user,
+                start_time: lock_period.start_time,
+                end_time: lock_period.end_time,
+                lock_type: lock_period.lock_type.clone(),
+            });
+
+            msg!("Lock period added: {:?}", lock_period);
+        }
+        ComplianceAction::UpdateLockPeriod { index, lock_period } => {
+            compliance.update_lock_period(index, lock_period.clone())?;
+
+            emit!(LockPeriodUpdated {
+                mint: compliance.mint,
+                index,
+                user: lock_period.user,
+                start_time: lock_period.start_time,
+                end_time: lock_period.end_time,
+                lock_type: lock_period.lock_type.clone(),
+            });
+
+            msg!("Lock period {} updated: {:?}", index, lock_period);
+        }
+        ComplianceAction::RemoveLockPeriod { index } => {
+            let removed = compliance.remove_lock_period(index)?;
+
+            emit!(LockPeriodRemoved {
+                mint: compliance.mint,
+                index,
+                user: removed.user,
+            });
+
+            msg!("Lock period {} removed", index);
+        }
+        ComplianceAction::AddCountryRestriction { restriction } => {
+            compliance.add_country_restriction(restriction.clone())?;
+
+            emit!(CountryRestrictionAdded {
+                mint: compliance.mint,
+                country_code: restriction.country_code,
+                restriction_type: restriction.restriction_type.clone(),
+            });
+
+            msg!("Country restriction added: {:?}", restriction);
+        }
+        ComplianceAction::RemoveCountryRestriction { country_code } => {
+            compliance.remove_country_restriction(country_code)?;
+
+            emit!(CountryRestrictionRemoved {
+                mint: compliance.mint,
+                country_code,
+            });
+
+            msg!("Country restriction removed: {}", country_code);
+        }
+        ComplianceAction::SetTransferLimits { limits } => {
+            compliance.set_transfer_limits(limits.clone())?;
+
+            emit!(TransferLimitsUpdated {
+                mint: compliance.mint,
+                min_transfer_amount: limits.min_transfer_amount,
+                max_transfer_amount: limits.max_transfer_amount,
+                daily_limit: limits.daily_limit,
+                monthly_limit: limits.monthly_limit,
+                reset_period: limits.reset_period,
+            });
+
+            msg!("Transfer limits updated: {:?}", limits);
+        }
+        ComplianceAction::SetHoldingLimits { limits } => {
+            compliance.set_holding_limits(limits.clone())?;
+
+            emit!(HoldingLimitsUpdated {
+                mint: compliance.mint,
+                min_holding_amount: limits.min_holding_amount,
+                max_holding_amount: limits.max_holding_amount,
+                max_holding_percentage: limits.max_holding_percentage,
+            });
+
+            msg!("Holding limits updated: {:?}", limits);
+        }
+        ComplianceAction::AddTradingWindow { window } => {
+            compliance.add_trading_window(window.clone())?;
+
+            emit!(TradingWindowAdded {
+                mint: compliance.mint,
+                index: (compliance.trading_windows.len() - 1) as u8,
+                start_time: window.start_time,
+                end_time: window.end_time,
+                days_of_week: window.days_of_week,
+            });
+
+            msg!("Trading window added: {:?}", window);
+        }
+        ComplianceAction::RemoveTradingWindow { index } => {
+            compliance.remove_trading_window(index)?;
+
+            emit!(TradingWindowRemoved {
+                mint: compliance.mint,
+                index,
+            });
+
+            msg!("Trading window {} removed", index);
+        }
+        ComplianceAction::SetMaxInvestors { max_investors } => {
+            compliance.set_max_investors(max_investors)?;
+
+            emit!(MaxInvestorsUpdated {
+                mint: compliance.mint,
+                max_investors,
+            });
+
+            msg!("Max investors set to: {:?}", max_investors);
+        }
+        ComplianceAction::SetTransferRequestTtl { ttl } => {
+            compliance.set_transfer_request_ttl(ttl)?;
+
+            emit!(TransferRequestTtlUpdated {
+                mint: compliance.mint,
+                ttl,
+            });
+
+            msg!("Transfer request TTL set to: {} seconds", ttl);
+        }
+        ComplianceAction::SetCountryInvestorLimit { country_code, max_investors } => {
+            compliance.set_country_investor_limit(country_code, max_investors, current_investors)?;
+
+            emit!(CountryInvestorLimitUpdated {
+                mint: compliance.mint,
+                country_code,
+                max_investors,
+                current_investors: compliance
+                    .country_investor_limits
+                    .iter()
+                    .find(|l| l.country_code == country_code)
+                    .map_or(0, |l| l.current_investors),
+            });
+
+            msg!("Investor cap for country {} set to: {:?}", country_code, max_investors);
+        }
+        ComplianceAction::AddComplianceModule {
+            name,
+            module_type,
+            program_id,
+            account_count,
+            parameters,
+        } => {
+            let module_id = compliance.add_module(ComplianceModule {
+                id: 0,
+                name: name.clone(),
+                module_type: module_type.clone(),
+                is_active: true,
+                parameters,
+                program_id,
+                account_count,
+            })?;
+
+            emit!(ComplianceModuleAdded {
+                module: program_id,
+                module_type: format!("{:?}", module_type),
+            });
+
+            msg!("Compliance module {} added: {} ({:?})", module_id, name, module_type);
+        }
+        ComplianceAction::RemoveComplianceModule { module_id } => {
+            let module = compliance
+                .modules
+                .iter()
+                .find(|m| m.id == module_id)
+                .cloned()
+                .ok_or(ERC3643Error::ComplianceModuleNotFound)?;
+            compliance.remove_module(module_id)?;
+
+            emit!(ComplianceModuleRemoved {
+                module: module.program_id,
+                module_type: format!("{:?}", module.module_type),
+            });
+
+            msg!("Compliance module {} removed: {}", module_id, module.name);
+        }
+        ComplianceAction::SetTimelockDelay { delay } => {
+            compliance.set_timelock_delay(delay)?;
+
+            emit!(ComplianceTimelockUpdated {
+                mint: compliance.mint,
+                delay,
+            });
+
+            msg!("Compliance timelock set to: {} seconds", delay);
+        }
+    }
+
+    Ok(())
+}
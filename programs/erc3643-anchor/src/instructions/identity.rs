@@ -3,9 +3,16 @@ use crate::{
     error::ERC3643Error,
     events::*,
     state::*,
+    utils::{
+        load_identity_claims, split_claim_accounts, unverify_uncovered_registries,
+        verify_claim_signature, verify_required_claims_coverage,
+    },
 };
 
 /// Register a new identity in the registry
+///
+/// Creates the user's shared identity storage if it does not exist yet and
+/// binds this token's identity registry to it.
 #[derive(Accounts)]
 #[instruction(user: Pubkey, country: u16)]
 pub struct RegisterIdentity<'info> {
@@ -19,24 +26,51 @@ pub struct RegisterIdentity<'info> {
     /// Token configuration
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
     /// The mint this identity is for
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::IdentityAgent) @ ERC3643Error::IdentityAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Shared identity storage for the user
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = IdentityStorage::LEN,
+        seeds = [IDENTITY_STORAGE_SEED, user.as_ref()],
+        bump
+    )]
+    pub identity_storage: Account<'info, IdentityStorage>,
+
     /// Identity registry account to create
     #[account(
         init,
         payer = payer,
         space = IdentityRegistry::LEN,
-        seeds = [IDENTITY_SEED, user.as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), user.as_ref()],
         bump
     )]
     pub identity_registry: Account<'info, IdentityRegistry>,
 
+    /// Transfer volume counter for the user
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TransferCounter::LEN,
+        seeds = [TRANSFER_COUNTER_SEED, mint.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub transfer_counter: Account<'info, TransferCounter>,
+
     /// System program
     pub system_program: Program<'info, System>,
 }
@@ -46,6 +80,7 @@ pub fn register_identity(
     user: Pubkey,
     country: u16,
 ) -> Result<()> {
+    let storage = &mut ctx.accounts.identity_storage;
     let identity = &mut ctx.accounts.identity_registry;
     let agent = &ctx.accounts.agent;
 
@@ -55,18 +90,46 @@ pub fn register_identity(
 
     let now = Clock::get()?.unix_timestamp;
 
+    // Initialize the shared storage on first registration
+    if storage.user == Pubkey::default() {
+        storage.user = user;
+        storage.identity = user;
+        storage.country = country;
+        storage.onchain_id = None;
+        storage.bound_registries = 0;
+        storage.created_at = now;
+        storage.updated_at = now;
+        storage.created_by = agent.key();
+        storage.reserved = [0; 32];
+    }
+
     // Initialize identity registry
     identity.user = user;
     identity.is_verified = false; // Will be set to true once required claims are added
     identity.country = country;
-    identity.onchain_id = None;
+    identity.onchain_id = storage.onchain_id;
     identity.claims_count = 0;
     identity.required_claims_met = false;
     identity.registered_at = now;
     identity.last_verified_at = 0;
     identity.updated_at = now;
     identity.registered_by = agent.key();
-    identity.reserved = [0; 64];
+    identity.identity = storage.identity;
+    identity.mint = ctx.accounts.mint.key();
+    identity.storage = storage.key();
+    identity.reserved = [0; 32];
+
+    storage.bind()?;
+
+    ctx.accounts
+        .transfer_counter
+        .initialize_if_new(ctx.accounts.mint.key(), user);
+
+    emit!(IdentityRegistryBound {
+        identity_storage: storage.key(),
+        identity_registry: identity.key(),
+        mint: identity.mint,
+    });
 
     emit!(IdentityRegistered {
         investor_address: user,
@@ -80,16 +143,194 @@ pub fn register_identity(
     Ok(())
 }
 
+/// Bind this token's identity registry to a user's existing identity storage
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct BindIdentityStorage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Agent or owner performing the binding
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint to bind
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::IdentityAgent) @ ERC3643Error::IdentityAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Shared identity storage for the user
+    #[account(
+        mut,
+        seeds = [IDENTITY_STORAGE_SEED, user.as_ref()],
+        bump
+    )]
+    pub identity_storage: Account<'info, IdentityStorage>,
+
+    /// Identity registry account to create
+    #[account(
+        init,
+        payer = payer,
+        space = IdentityRegistry::LEN,
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Transfer volume counter for the user
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TransferCounter::LEN,
+        seeds = [TRANSFER_COUNTER_SEED, mint.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub transfer_counter: Account<'info, TransferCounter>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn bind_identity_storage(ctx: Context<BindIdentityStorage>, user: Pubkey) -> Result<()> {
+    let storage = &mut ctx.accounts.identity_storage;
+    let identity = &mut ctx.accounts.identity_registry;
+    let agent = &ctx.accounts.agent;
+
+    let now = Clock::get()?.unix_timestamp;
+
+    // Seed the registry from the shared storage; verification is per token
+    identity.user = user;
+    identity.is_verified = false;
+    identity.country = storage.country;
+    identity.onchain_id = storage.onchain_id;
+    identity.claims_count = 0;
+    identity.required_claims_met = false;
+    identity.registered_at = now;
+    identity.last_verified_at = 0;
+    identity.updated_at = now;
+    identity.registered_by = agent.key();
+    identity.identity = storage.identity;
+    identity.mint = ctx.accounts.mint.key();
+    identity.storage = storage.key();
+    identity.reserved = [0; 32];
+
+    storage.bind()?;
+
+    ctx.accounts
+        .transfer_counter
+        .initialize_if_new(ctx.accounts.mint.key(), user);
+
+    emit!(IdentityRegistryBound {
+        identity_storage: storage.key(),
+        identity_registry: identity.key(),
+        mint: identity.mint,
+    });
+
+    emit!(IdentityRegistered {
+        investor_address: user,
+        identity: identity.key(),
+    });
+
+    msg!("Identity storage of {} bound to mint {}", user, identity.mint);
+    msg!("Bound registries: {}", storage.bound_registries);
+
+    Ok(())
+}
+
+/// Unbind this token's identity registry from a user's identity storage
+#[derive(Accounts)]
+pub struct UnbindIdentityStorage<'info> {
+    /// Agent or owner performing the unbinding
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint to unbind
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::IdentityAgent) @ ERC3643Error::IdentityAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Shared identity storage for the user
+    #[account(
+        mut,
+        seeds = [IDENTITY_STORAGE_SEED, identity_registry.user.as_ref()],
+        bump
+    )]
+    pub identity_storage: Account<'info, IdentityStorage>,
+
+    /// Identity registry to close
+    #[account(
+        mut,
+        close = agent,
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
+        bump,
+        constraint = identity_registry.storage == identity_storage.key() @ ERC3643Error::IdentityStorageMismatch
+    )]
+    pub identity_registry: Account<'info, IdentityRegistry>,
+}
+
+pub fn unbind_identity_storage(ctx: Context<UnbindIdentityStorage>) -> Result<()> {
+    let storage = &mut ctx.accounts.identity_storage;
+    let identity = &ctx.accounts.identity_registry;
+
+    storage.unbind()?;
+
+    emit!(IdentityRegistryUnbound {
+        identity_storage: storage.key(),
+        identity_registry: identity.key(),
+        mint: identity.mint,
+    });
+
+    emit!(IdentityRemoved {
+        investor_address: identity.user,
+        identity: identity.key(),
+    });
+
+    msg!("Identity storage of {} unbound from mint {}", identity.user, identity.mint);
+    msg!("Bound registries: {}", storage.bound_registries);
+
+    Ok(())
+}
+
 /// Add a claim to an identity
+///
+/// A removed claim can be added again, signed at the tombstone's next nonce.
+/// The identity's other claim accounts must be passed as remaining accounts
+/// so verification can be recomputed.
 #[derive(Accounts)]
 #[instruction(topic: u64, scheme: u64, data: Vec<u8>, signature: Vec<u8>, uri: String)]
 pub struct AddClaim<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// The issuer adding the claim
-    #[account(mut)]
-    pub issuer: Signer<'info>,
+    /// The issuer of the claim
+    /// CHECK: Authenticated by the claim signature (or as a signer for contract claims)
+    pub issuer: UncheckedAccount<'info>,
 
     /// Token configuration
     #[account(
@@ -104,7 +345,7 @@ pub struct AddClaim<'info> {
     /// Identity registry for the user
     #[account(
         mut,
-        seeds = [IDENTITY_SEED, identity_registry.user.as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
         bump
     )]
     pub identity_registry: Account<'info, IdentityRegistry>,
@@ -123,16 +364,21 @@ pub struct AddClaim<'info> {
     )]
     pub claim_topics_registry: Account<'info, ClaimTopicsRegistry>,
 
-    /// The claim account to create
+    /// The claim account to create, or the tombstone of a removed claim
     #[account(
-        init,
+        init_if_needed,
         payer = payer,
         space = Claim::LEN,
-        seeds = [CLAIM_SEED, identity_registry.user.as_ref(), issuer.key().as_ref(), &topic.to_le_bytes()],
+        seeds = [CLAIM_SEED, identity_registry.identity.as_ref(), issuer.key().as_ref(), &topic.to_le_bytes()],
         bump
     )]
     pub claim: Account<'info, Claim>,
 
+    /// Instructions sysvar, used to find the Ed25519 signature verification
+    /// CHECK: Address is checked against the instructions sysvar id
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
     /// System program
     pub system_program: Program<'info, System>,
 }
@@ -144,6 +390,7 @@ pub fn add_claim(
     data: Vec<u8>,
     signature: Vec<u8>,
     uri: String,
+    expires_at: Option<i64>,
 ) -> Result<()> {
     let claim = &mut ctx.accounts.claim;
     let identity = &mut ctx.accounts.identity_registry;
@@ -157,54 +404,57 @@ pub fn add_claim(
     );
 
     // Convert scheme to ClaimScheme enum
-    let claim_scheme = match scheme {
-        1 => ClaimScheme::ECDSA,
-        2 => ClaimScheme::RSA,
-        3 => ClaimScheme::Contract,
-        _ => return Err(ERC3643Error::InvalidArgument.into()),
+    let claim_scheme = ClaimScheme::from_u64(scheme)?;
+
+    // A live claim cannot be replaced; a removed one continues its nonce
+    let nonce = if claim.is_removed() {
+        claim.nonce.checked_add(1).ok_or(ERC3643Error::ArithmeticOverflow)?
+    } else {
+        require!(claim.issuer == Pubkey::default(), ERC3643Error::ClaimAlreadyExists);
+        0
     };
 
+    // Verify the issuer signed (identity, topic, nonce, expiry, data)
+    verify_claim_signature(
+        &claim_scheme,
+        &issuer.to_account_info(),
+        &Claim::signed_message(&identity.identity, topic, nonce, expires_at, &data),
+        &signature,
+        &ctx.accounts.instructions_sysvar.to_account_info(),
+    )?;
+
     // Create the claim
     let new_claim = Claim::new(
-        identity.user,
+        identity.identity,
         issuer.key(),
         topic,
         claim_scheme,
         data.clone(),
         signature.clone(),
         uri.clone(),
-        None, // No expiration for now
+        expires_at,
     )?;
 
     **claim = new_claim;
+    claim.nonce = nonce;
 
     // Update identity claims count
     identity.claims_count = identity.claims_count
         .checked_add(1)
         .ok_or(ERC3643Error::ArithmeticOverflow)?;
 
-    // Check if this claim helps meet required claims
-    let claim_topics = &ctx.accounts.claim_topics_registry;
-    let required_topics = claim_topics.get_required_topics();
-    
-    // Simple verification: if we have at least one valid claim for each required topic
-    let mut topics_met = 0;
-    for required_topic in required_topics {
-        // In a full implementation, we'd check all claims for this identity
-        // For now, we'll just check if this new claim matches a required topic
-        if topic == *required_topic {
-            topics_met += 1;
-        }
-    }
-
-    // Update verification status if all required topics are met
-    if required_topics.is_empty() || topics_met > 0 {
-        identity.required_claims_met = true;
-        identity.is_verified = true;
-        identity.last_verified_at = Clock::get()?.unix_timestamp;
-    }
+    // Re-evaluate coverage of the required topics across all claims
+    let mut claims = load_identity_claims(ctx.remaining_accounts, &identity.identity)?;
+    claims.push((**claim).clone());
+    let verification = verify_required_claims_coverage(
+        &identity.identity,
+        &claims,
+        ctx.accounts.claim_topics_registry.get_required_topics(),
+        trusted_issuers,
+    );
 
-    identity.updated_at = Clock::get()?.unix_timestamp;
+    identity.update_required_claims_status(verification.is_verified)?;
+    identity.update_verification(verification.is_verified)?;
 
     let claim_id = claim.generate_id();
 
@@ -225,12 +475,152 @@ pub fn add_claim(
     Ok(())
 }
 
+/// Refresh a claim's data, signature and expiry
+///
+/// The issuer signs the new claim message at the claim's next nonce, the same
+/// way claims are added, so an older signed message cannot be replayed to
+/// roll the claim back. The identity's
+/// other claim accounts may be passed as remaining accounts to mark the
+/// identity verified once they cover the required topics.
+#[derive(Accounts)]
+pub struct UpdateClaim<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The issuer of the claim
+    /// CHECK: Authenticated by the claim signature (or as a signer for contract claims)
+    #[account(address = claim.issuer @ ERC3643Error::Unauthorized)]
+    pub issuer: UncheckedAccount<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint this claim is for
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Identity registry for the user
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
+        bump
+    )]
+    pub identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Trusted issuers registry
+    #[account(
+        seeds = [TRUSTED_ISSUERS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub trusted_issuers_registry: Account<'info, TrustedIssuersRegistry>,
+
+    /// Claim topics registry
+    #[account(
+        seeds = [CLAIM_TOPICS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub claim_topics_registry: Account<'info, ClaimTopicsRegistry>,
+
+    /// The claim to update
+    #[account(
+        mut,
+        seeds = [CLAIM_SEED, identity_registry.identity.as_ref(), claim.issuer.as_ref(), &claim.topic.to_le_bytes()],
+        bump
+    )]
+    pub claim: Account<'info, Claim>,
+
+    /// Instructions sysvar, used to find the Ed25519 signature verification
+    /// CHECK: Address is checked against the instructions sysvar id
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+}
+
+pub fn update_claim(
+    ctx: Context<UpdateClaim>,
+    data: Vec<u8>,
+    signature: Vec<u8>,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    let claim = &mut ctx.accounts.claim;
+    let identity = &mut ctx.accounts.identity_registry;
+    let trusted_issuers = &ctx.accounts.trusted_issuers_registry;
+    let issuer = &ctx.accounts.issuer;
+
+    // The issuer must still be trusted for the topic
+    require!(
+        trusted_issuers.is_trusted_for_topic(&issuer.key(), claim.topic),
+        ERC3643Error::IssuerRoleRequired
+    );
+
+    // Verify the issuer signed the refreshed claim at the next nonce
+    let nonce = claim.nonce.checked_add(1).ok_or(ERC3643Error::ArithmeticOverflow)?;
+    verify_claim_signature(
+        &claim.scheme,
+        &issuer.to_account_info(),
+        &Claim::signed_message(&identity.identity, claim.topic, nonce, expires_at, &data),
+        &signature,
+        &ctx.accounts.instructions_sysvar.to_account_info(),
+    )?;
+
+    claim.update_data(data.clone(), signature.clone(), expires_at)?;
+
+    // Re-evaluate coverage of the required topics across all claims
+    let claim_key = claim.key();
+    let other_claims: Vec<AccountInfo> = ctx
+        .remaining_accounts
+        .iter()
+        .filter(|account| account.key() != claim_key)
+        .cloned()
+        .collect();
+    let mut claims = load_identity_claims(&other_claims, &identity.identity)?;
+    claims.push((**claim).clone());
+    let verification = verify_required_claims_coverage(
+        &identity.identity,
+        &claims,
+        ctx.accounts.claim_topics_registry.get_required_topics(),
+        trusted_issuers,
+    );
+
+    // The refreshed claim stays valid, so coverage cannot shrink; claims
+    // left out of the remaining accounts must not unverify the identity
+    if verification.is_verified {
+        identity.update_required_claims_status(true)?;
+        identity.update_verification(true)?;
+    }
+
+    emit!(ClaimChanged {
+        claim_id: claim.generate_id(),
+        topic: claim.topic,
+        scheme: claim.scheme.to_u64(),
+        issuer: issuer.key(),
+        signature,
+        data,
+        uri: claim.uri.clone(),
+    });
+
+    msg!("Claim updated for identity: {}", identity.user);
+    msg!("Topic: {}, Expires at: {:?}", claim.topic, expires_at);
+    msg!("Identity verified: {}", identity.is_verified);
+
+    Ok(())
+}
+
 /// Remove a claim from an identity
+///
+/// The claim account is kept as a tombstone so the issuer's signed message
+/// cannot be replayed through `add_claim`.
+/// The identity's other claims for the claim's topic must be passed as
+/// remaining accounts so coverage can be rechecked, followed by the identity
+/// registry, trusted issuers registry and claim topics registry of every
+/// other token the identity is bound to, since the claim counts for all of
+/// them.
 #[derive(Accounts)]
 #[instruction(claim_id: [u8; 32])]
 pub struct RemoveClaim<'info> {
     /// The issuer or identity owner removing the claim
-    #[account(mut)]
     pub authority: Signer<'info>,
 
     /// Token configuration
@@ -246,16 +636,33 @@ pub struct RemoveClaim<'info> {
     /// Identity registry for the user
     #[account(
         mut,
-        seeds = [IDENTITY_SEED, identity_registry.user.as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
         bump
     )]
     pub identity_registry: Account<'info, IdentityRegistry>,
 
-    /// The claim account to close
+    /// Shared identity storage the registry is bound to
+    #[account(address = identity_registry.storage @ ERC3643Error::IdentityStorageMismatch)]
+    pub identity_storage: Account<'info, IdentityStorage>,
+
+    /// Trusted issuers registry
+    #[account(
+        seeds = [TRUSTED_ISSUERS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub trusted_issuers_registry: Account<'info, TrustedIssuersRegistry>,
+
+    /// Claim topics registry
+    #[account(
+        seeds = [CLAIM_TOPICS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub claim_topics_registry: Account<'info, ClaimTopicsRegistry>,
+
+    /// The claim to remove
     #[account(
         mut,
-        close = authority,
-        seeds = [CLAIM_SEED, identity_registry.user.as_ref(), claim.issuer.as_ref(), &claim.topic.to_le_bytes()],
+        seeds = [CLAIM_SEED, identity_registry.identity.as_ref(), claim.issuer.as_ref(), &claim.topic.to_le_bytes()],
         bump,
         constraint = claim.issuer == authority.key() || identity_registry.user == authority.key() @ ERC3643Error::Unauthorized
     )]
@@ -263,10 +670,12 @@ pub struct RemoveClaim<'info> {
 }
 
 pub fn remove_claim(ctx: Context<RemoveClaim>, _claim_id: [u8; 32]) -> Result<()> {
-    let claim = &ctx.accounts.claim;
+    let claim = &mut ctx.accounts.claim;
     let identity = &mut ctx.accounts.identity_registry;
     let authority = &ctx.accounts.authority;
 
+    claim.remove()?;
+
     // Store claim info for event
     let claim_id = claim.generate_id();
     let topic = claim.topic;
@@ -276,26 +685,47 @@ pub fn remove_claim(ctx: Context<RemoveClaim>, _claim_id: [u8; 32]) -> Result<()
     let data = claim.data.clone();
     let uri = claim.uri.clone();
 
-    // Update identity claims count
-    identity.claims_count = identity.claims_count
-        .checked_sub(1)
-        .ok_or(ERC3643Error::ArithmeticUnderflow)?;
+    // The claim may have been added through another token's registry
+    identity.claims_count = identity.claims_count.saturating_sub(1);
+
+    // Only a required topic left without another valid claim unverifies
+    // the identity
+    let claim_key = claim.key();
+    let (claim_accounts, bound_accounts) = split_claim_accounts(ctx.remaining_accounts);
+    let other_claims: Vec<AccountInfo> = claim_accounts
+        .iter()
+        .filter(|account| account.key() != claim_key)
+        .cloned()
+        .collect();
+    let claims = load_identity_claims(&other_claims, &identity.identity)?;
+    if ctx.accounts.claim_topics_registry.is_topic_required(topic) {
+        let coverage = verify_required_claims_coverage(
+            &identity.identity,
+            &claims,
+            &[topic],
+            &ctx.accounts.trusted_issuers_registry,
+        );
+
+        if !coverage.is_verified {
+            identity.update_required_claims_status(false)?;
+            identity.update_verification(false)?;
+        }
+    }
 
-    // Re-evaluate verification status
-    // In a full implementation, we'd check all remaining claims
-    // For now, we'll be conservative and mark as unverified if any claim is removed
-    identity.is_verified = false;
-    identity.required_claims_met = false;
-    identity.updated_at = Clock::get()?.unix_timestamp;
+    // The claim is shared with the identity's registries of other tokens
+    unverify_uncovered_registries(
+        bound_accounts,
+        &ctx.accounts.identity_storage.key(),
+        &ctx.accounts.identity_storage,
+        &ctx.accounts.mint.key(),
+        &claims,
+        topic,
+    )?;
 
     emit!(ClaimRemoved {
         claim_id,
         topic,
-        scheme: match scheme {
-            ClaimScheme::ECDSA => 1,
-            ClaimScheme::RSA => 2,
-            ClaimScheme::Contract => 3,
-        },
+        scheme: scheme.to_u64(),
         issuer,
         signature,
         data,
@@ -309,6 +739,123 @@ pub fn remove_claim(ctx: Context<RemoveClaim>, _claim_id: [u8; 32]) -> Result<()
     Ok(())
 }
 
+/// Revoke a claim (issuer or token owner)
+///
+/// The claim account is kept as a record of the revocation. The identity's
+/// other claim accounts must be passed as remaining accounts so verification
+/// can be recomputed, followed by the identity registry, trusted issuers
+/// registry and claim topics registry of every other token the identity is
+/// bound to, since the claim counts for all of them.
+#[derive(Accounts)]
+pub struct RevokeClaim<'info> {
+    /// The claim issuer or token owner revoking the claim
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint this claim is for
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Identity registry for the user
+    #[account(
+        mut,
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
+        bump
+    )]
+    pub identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Shared identity storage the registry is bound to
+    #[account(address = identity_registry.storage @ ERC3643Error::IdentityStorageMismatch)]
+    pub identity_storage: Account<'info, IdentityStorage>,
+
+    /// Trusted issuers registry
+    #[account(
+        seeds = [TRUSTED_ISSUERS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub trusted_issuers_registry: Account<'info, TrustedIssuersRegistry>,
+
+    /// Claim topics registry
+    #[account(
+        seeds = [CLAIM_TOPICS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub claim_topics_registry: Account<'info, ClaimTopicsRegistry>,
+
+    /// The claim to revoke
+    #[account(
+        mut,
+        seeds = [CLAIM_SEED, identity_registry.identity.as_ref(), claim.issuer.as_ref(), &claim.topic.to_le_bytes()],
+        bump,
+        constraint = claim.issuer == authority.key() || config.is_owner(&authority.key()) @ ERC3643Error::Unauthorized
+    )]
+    pub claim: Account<'info, Claim>,
+}
+
+pub fn revoke_claim(ctx: Context<RevokeClaim>) -> Result<()> {
+    let claim = &mut ctx.accounts.claim;
+    let identity = &mut ctx.accounts.identity_registry;
+    let authority = &ctx.accounts.authority;
+
+    claim.revoke(authority.key())?;
+
+    // Re-evaluate verification status now that the claim no longer counts
+    let claim_key = claim.key();
+    let (claim_accounts, bound_accounts) = split_claim_accounts(ctx.remaining_accounts);
+    let other_claims: Vec<AccountInfo> = claim_accounts
+        .iter()
+        .filter(|account| account.key() != claim_key)
+        .cloned()
+        .collect();
+    let claims = load_identity_claims(&other_claims, &identity.identity)?;
+    let verification = verify_required_claims_coverage(
+        &identity.identity,
+        &claims,
+        ctx.accounts.claim_topics_registry.get_required_topics(),
+        &ctx.accounts.trusted_issuers_registry,
+    );
+
+    identity.update_required_claims_status(verification.is_verified)?;
+    identity.update_verification(verification.is_verified)?;
+
+    // The claim is shared with the identity's registries of other tokens
+    unverify_uncovered_registries(
+        bound_accounts,
+        &ctx.accounts.identity_storage.key(),
+        &ctx.accounts.identity_storage,
+        &ctx.accounts.mint.key(),
+        &claims,
+        claim.topic,
+    )?;
+
+    emit!(ClaimRevoked {
+        signature: claim.signature.clone(),
+    });
+
+    msg!("Claim revoked for identity: {}", identity.user);
+    msg!("Topic: {}, Revoked by: {}", claim.topic, authority.key());
+    msg!("Identity verification status updated: {}", identity.is_verified);
+
+    Ok(())
+}
+
+/// Check whether a claim has been revoked (read-only)
+#[derive(Accounts)]
+pub struct IsClaimRevoked<'info> {
+    /// The claim to check
+    pub claim: Account<'info, Claim>,
+}
+
+pub fn is_claim_revoked(ctx: Context<IsClaimRevoked>) -> Result<bool> {
+    Ok(ctx.accounts.claim.is_revoked)
+}
+
 /// Update identity country
 #[derive(Accounts)]
 pub struct UpdateCountry<'info> {
@@ -319,18 +866,25 @@ pub struct UpdateCountry<'info> {
     /// Token configuration
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::IdentityAgent) @ ERC3643Error::IdentityAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Identity registry to update
     #[account(
         mut,
-        seeds = [IDENTITY_SEED, identity_registry.user.as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
         bump
     )]
     pub identity_registry: Account<'info, IdentityRegistry>,
@@ -352,6 +906,9 @@ pub fn update_country(ctx: Context<UpdateCountry>, country: u16) -> Result<()> {
 }
 
 /// Delete an identity from the registry
+///
+/// Unbinds this token's identity registry and closes the shared identity
+/// storage once no other token is bound to it.
 #[derive(Accounts)]
 pub struct DeleteIdentity<'info> {
     /// Agent performing the deletion
@@ -361,33 +918,64 @@ pub struct DeleteIdentity<'info> {
     /// Token configuration
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
     /// The mint
     pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::IdentityAgent) @ ERC3643Error::IdentityAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Shared identity storage for the user
+    #[account(
+        mut,
+        seeds = [IDENTITY_STORAGE_SEED, identity_registry.user.as_ref()],
+        bump
+    )]
+    pub identity_storage: Account<'info, IdentityStorage>,
+
     /// Identity registry to delete
     #[account(
         mut,
         close = agent,
-        seeds = [IDENTITY_SEED, identity_registry.user.as_ref()],
-        bump
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), identity_registry.user.as_ref()],
+        bump,
+        constraint = identity_registry.storage == identity_storage.key() @ ERC3643Error::IdentityStorageMismatch
     )]
     pub identity_registry: Account<'info, IdentityRegistry>,
 }
 
 pub fn delete_identity(ctx: Context<DeleteIdentity>) -> Result<()> {
+    let storage = &mut ctx.accounts.identity_storage;
     let identity = &ctx.accounts.identity_registry;
     let user = identity.user;
 
+    storage.unbind()?;
+
+    emit!(IdentityRegistryUnbound {
+        identity_storage: storage.key(),
+        identity_registry: identity.key(),
+        mint: identity.mint,
+    });
+
     emit!(IdentityRemoved {
         investor_address: user,
         identity: identity.key(),
     });
 
+    // No token references the storage anymore
+    if storage.bound_registries == 0 {
+        storage.close(ctx.accounts.agent.to_account_info())?;
+        msg!("Identity storage closed for user: {}", user);
+    }
+
     msg!("Identity deleted for user: {}", user);
 
     Ok(())
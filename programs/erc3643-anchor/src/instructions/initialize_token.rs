@@ -1,7 +1,22 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token_2022::{self, Token2022},
-    token_interface::{Mint, TokenAccount},
+    token_2022::{
+        initialize_mint2,
+        spl_token_2022::{
+            extension::ExtensionType,
+            state::{AccountState, Mint as MintState},
+        },
+        InitializeMint2, Token2022,
+    },
+    token_2022_extensions::{
+        default_account_state_initialize, metadata_pointer_initialize,
+        permanent_delegate_initialize, spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_metadata_interface::state::TokenMetadata, token_metadata_initialize,
+        transfer_hook_initialize, transfer_hook_update, DefaultAccountStateInitialize,
+        MetadataPointerInitialize, PermanentDelegateInitialize, TokenMetadataInitialize,
+        TransferHookInitialize, TransferHookUpdate,
+    },
+    token_interface::Mint,
 };
 use crate::{
     error::ERC3643Error,
@@ -10,8 +25,11 @@ use crate::{
 };
 
 /// Initialize the ERC-3643 token with compliance and identity registry
+///
+/// The mint is created here as a Token-2022 mint with the TransferHook,
+/// PermanentDelegate, DefaultAccountState (frozen) and MetadataPointer /
+/// TokenMetadata extensions, with the config PDA as every authority.
 #[derive(Accounts)]
-#[instruction(name: String, symbol: String, decimals: u8)]
 pub struct InitializeToken<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -21,15 +39,8 @@ pub struct InitializeToken<'info> {
     pub owner: Signer<'info>,
 
     /// The mint account to be created
-    #[account(
-        init,
-        payer = payer,
-        mint::decimals = decimals,
-        mint::authority = config,
-        mint::freeze_authority = config,
-        mint::token_program = token_program,
-    )]
-    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub mint: Signer<'info>,
 
     /// Token configuration PDA
     #[account(
@@ -79,6 +90,21 @@ pub struct InitializeToken<'info> {
     )]
     pub compliance_registry: Account<'info, ComplianceRegistry>,
 
+    /// Role registry PDA
+    #[account(
+        init,
+        payer = payer,
+        space = RoleRegistry::LEN,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Transfer hook program enforcing compliance on every transfer
+    /// CHECK: Must be an executable program
+    #[account(executable)]
+    pub transfer_hook_program: UncheckedAccount<'info>,
+
     /// Token program (Token-2022)
     pub token_program: Program<'info, Token2022>,
     
@@ -93,20 +119,26 @@ pub fn handler(
     ctx: Context<InitializeToken>,
     name: String,
     symbol: String,
+    uri: String,
     decimals: u8,
     initial_supply: Option<u64>,
 ) -> Result<()> {
+    // Validate inputs
+    validate_string_length(&name, MAX_NAME_LENGTH, ERC3643Error::NameTooLong)?;
+    validate_string_length(&symbol, MAX_SYMBOL_LENGTH, ERC3643Error::SymbolTooLong)?;
+    validate_string_length(&uri, MAX_URI_LENGTH, ERC3643Error::InvalidURIFormat)?;
+    validate_decimals(decimals)?;
+
+    create_mint_with_extensions(&ctx, &name, &symbol, &uri, decimals)?;
+
     let mint = &ctx.accounts.mint;
     let config = &mut ctx.accounts.config;
     let trusted_issuers = &mut ctx.accounts.trusted_issuers_registry;
     let claim_topics = &mut ctx.accounts.claim_topics_registry;
     let compliance = &mut ctx.accounts.compliance_registry;
+    let roles = &mut ctx.accounts.role_registry;
     let owner = &ctx.accounts.owner;
-
-    // Validate inputs
-    validate_string_length(&name, MAX_NAME_LENGTH, ERC3643Error::NameTooLong)?;
-    validate_string_length(&symbol, MAX_SYMBOL_LENGTH, ERC3643Error::SymbolTooLong)?;
-    validate_decimals(decimals)?;
+    let hook_program = ctx.accounts.transfer_hook_program.key();
 
     let now = Clock::get()?.unix_timestamp;
 
@@ -119,8 +151,8 @@ pub fn handler(
     config.onchain_id = Pubkey::default(); // Can be set later
     config.owner = owner.key();
     config.agents = Vec::new();
-    config.transfer_hook_program = None; // Will be set when transfer hook is configured
-    config.enforcement_mode = EnforcementMode::ProgramControlled; // Start with fallback mode
+    config.transfer_hook_program = Some(hook_program);
+    config.enforcement_mode = EnforcementMode::TransferHook;
     config.paused = true; // Start paused for safety
     config.total_supply = 0;
     config.require_identity_verification = true;
@@ -131,7 +163,11 @@ pub fn handler(
     config.max_holding = None;
     config.created_at = now;
     config.updated_at = now;
-    config.reserved = [0; 128];
+    config.compliance_manager = Pubkey::default();
+    config.max_supply = None;
+    config.pending_owner = Pubkey::default();
+    config.multisig = Pubkey::default();
+    config.reserved = [0; 23];
 
     // Initialize trusted issuers registry
     trusted_issuers.mint = mint.key();
@@ -167,13 +203,24 @@ pub fn handler(
     compliance.trading_windows = Vec::new();
     compliance.max_investors = None;
     compliance.current_investors = 0;
+    compliance.country_investor_limits = Vec::new();
     compliance.require_whitelist = false;
     compliance.enable_blacklist = false;
     compliance.require_kyc = true;
     compliance.require_aml = true;
     compliance.created_at = now;
     compliance.updated_at = now;
-    compliance.reserved = [0; 64];
+    compliance.transfer_request_ttl = 0;
+    compliance.timelock_delay = 0;
+    compliance.queued_change_count = 0;
+    compliance.reserved = [0; 40];
+
+    // Initialize role registry
+    roles.mint = mint.key();
+    roles.members = Vec::new();
+    roles.created_at = now;
+    roles.updated_at = now;
+    roles.reserved = [0; 32];
 
     // Mint initial supply if specified
     if let Some(supply) = initial_supply {
@@ -204,14 +251,150 @@ pub fn handler(
         compliance: compliance.key(),
     });
 
+    emit!(TransferHookConfigured {
+        mint: mint.key(),
+        hook_program,
+    });
+
     msg!("ERC-3643 token initialized: {} ({})", name, symbol);
     msg!("Mint: {}", mint.key());
     msg!("Owner: {}", owner.key());
+    msg!("Transfer hook: {}", hook_program);
     msg!("Token is paused - use unpause instruction to enable transfers");
 
     Ok(())
 }
 
+/// Create the Token-2022 mint and initialize its extensions. Every authority
+/// is the config PDA, and new token accounts start frozen until the holder is
+/// onboarded.
+fn create_mint_with_extensions(
+    ctx: &Context<InitializeToken>,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let config_key = ctx.accounts.config.key();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let hook_program = ctx.accounts.transfer_hook_program.key();
+
+    let mint_key = mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    // Size the account for the fixed-length extensions and fund it for the
+    // variable-length metadata that Token-2022 reallocates for later
+    let mint_len = ExtensionType::try_calculate_account_len::<MintState>(&[
+        ExtensionType::TransferHook,
+        ExtensionType::PermanentDelegate,
+        ExtensionType::DefaultAccountState,
+        ExtensionType::MetadataPointer,
+    ])?;
+    let metadata = TokenMetadata {
+        update_authority: OptionalNonZeroPubkey::try_from(Some(config_key))?,
+        mint: mint_key,
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+        uri: uri.to_string(),
+        additional_metadata: Vec::new(),
+    };
+    let lamports = Rent::get()?.minimum_balance(mint_len + metadata.tlv_size_of()?);
+
+    anchor_lang::system_program::create_account(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::CreateAccount {
+                from: ctx.accounts.payer.to_account_info(),
+                to: mint.to_account_info(),
+            },
+        ),
+        lamports,
+        mint_len as u64,
+        token_program.key,
+    )?;
+
+    // Extensions must be initialized before the mint itself
+    transfer_hook_initialize(
+        CpiContext::new(
+            token_program.clone(),
+            TransferHookInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.to_account_info(),
+            },
+        ),
+        Some(config_key),
+        Some(hook_program),
+    )?;
+
+    permanent_delegate_initialize(
+        CpiContext::new(
+            token_program.clone(),
+            PermanentDelegateInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.to_account_info(),
+            },
+        ),
+        &config_key,
+    )?;
+
+    default_account_state_initialize(
+        CpiContext::new(
+            token_program.clone(),
+            DefaultAccountStateInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.to_account_info(),
+            },
+        ),
+        &AccountState::Frozen,
+    )?;
+
+    metadata_pointer_initialize(
+        CpiContext::new(
+            token_program.clone(),
+            MetadataPointerInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.to_account_info(),
+            },
+        ),
+        Some(config_key),
+        Some(mint_key),
+    )?;
+
+    initialize_mint2(
+        CpiContext::new(
+            token_program.clone(),
+            InitializeMint2 {
+                mint: mint.to_account_info(),
+            },
+        ),
+        decimals,
+        &config_key,
+        Some(&config_key),
+    )?;
+
+    // Metadata lives in the mint itself and needs the mint authority to sign
+    token_metadata_initialize(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            TokenMetadataInitialize {
+                token_program_id: token_program.clone(),
+                metadata: mint.to_account_info(),
+                update_authority: ctx.accounts.config.to_account_info(),
+                mint_authority: ctx.accounts.config.to_account_info(),
+                mint: mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        name.to_string(),
+        symbol.to_string(),
+        uri.to_string(),
+    )?;
+
+    Ok(())
+}
+
 /// Set transfer hook program for Token-2022 enforcement
 #[derive(Accounts)]
 pub struct SetTransferHook<'info> {
@@ -229,10 +412,12 @@ pub struct SetTransferHook<'info> {
     pub config: Account<'info, TokenConfig>,
 
     /// The mint account
+    #[account(mut)]
     pub mint: InterfaceAccount<'info, Mint>,
 
     /// Transfer hook program
-    /// CHECK: This will be validated as a proper transfer hook program
+    /// CHECK: Must be an executable program
+    #[account(executable)]
     pub transfer_hook_program: UncheckedAccount<'info>,
 
     /// Token program
@@ -240,18 +425,46 @@ pub struct SetTransferHook<'info> {
 }
 
 pub fn set_transfer_hook(ctx: Context<SetTransferHook>) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    let hook_program = ctx.accounts.transfer_hook_program.key();
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_set_transfer_hook(
+        &mut ctx.accounts.config,
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.bumps.config,
+        ctx.accounts.transfer_hook_program.key(),
+    )
+}
+
+pub(crate) fn apply_set_transfer_hook<'info>(
+    config: &mut Account<'info, TokenConfig>,
+    mint: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    config_bump: u8,
+    hook_program: Pubkey,
+) -> Result<()> {
+    // Point the mint's TransferHook extension at the new program
+    let mint_key = mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[config_bump]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    transfer_hook_update(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            TransferHookUpdate {
+                token_program_id: token_program,
+                mint,
+                authority: config.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        Some(hook_program),
+    )?;
 
     // Update configuration
     config.transfer_hook_program = Some(hook_program);
     config.enforcement_mode = EnforcementMode::TransferHook;
     config.updated_at = Clock::get()?.unix_timestamp;
 
-    // In a real implementation, we would configure the Token-2022 mint
-    // to use the transfer hook extension here
-    // This requires calling the appropriate Token-2022 instructions
-
     emit!(TransferHookConfigured {
         mint: config.mint,
         hook_program,
@@ -286,8 +499,16 @@ pub fn set_enforcement_mode(
     ctx: Context<SetEnforcementMode>,
     mode: EnforcementMode,
 ) -> Result<()> {
-    let config = &mut ctx.accounts.config;
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    let owner = ctx.accounts.owner.key();
+    apply_set_enforcement_mode(&mut ctx.accounts.config, mode, owner)
+}
 
+pub(crate) fn apply_set_enforcement_mode(
+    config: &mut TokenConfig,
+    mode: EnforcementMode,
+    by: Pubkey,
+) -> Result<()> {
     // Validate mode is supported
     match mode {
         EnforcementMode::TransferHook | EnforcementMode::Hybrid => {
@@ -307,7 +528,7 @@ pub fn set_enforcement_mode(
     let enabled = matches!(mode, EnforcementMode::ProgramControlled);
     emit!(FallbackModeToggled {
         enabled,
-        changed_by: ctx.accounts.owner.key(),
+        changed_by: by,
     });
 
     msg!("Enforcement mode set to: {:?}", mode);
@@ -2,10 +2,20 @@ pub mod initialize_token;
 pub mod identity;
 pub mod token;
 pub mod compliance;
+pub mod compliance_rules;
 pub mod admin;
+pub mod transfer_request;
+pub mod batch;
+pub mod multisig;
+pub mod timelock;
 
 pub use initialize_token::*;
 pub use identity::*;
 pub use token::*;
 pub use compliance::*;
+pub use compliance_rules::*;
 pub use admin::*;
+pub use transfer_request::*;
+pub use batch::*;
+pub use multisig::*;
+pub use timelock::*;
@@ -0,0 +1,427 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::Token2022;
+use crate::{
+    error::ERC3643Error,
+    events::*,
+    instructions::admin::{
+        apply_add_agent, apply_add_claim_topic, apply_add_trusted_issuer,
+        apply_cancel_ownership_transfer, apply_grant_role, apply_remove_agent,
+        apply_remove_claim_topic, apply_remove_trusted_issuer, apply_revoke_role,
+        apply_set_compliance_manager, apply_set_max_supply, apply_set_pause_status,
+        apply_set_token_information, apply_transfer_ownership, TokenMetadataAccounts,
+    },
+    instructions::initialize_token::{apply_set_enforcement_mode, apply_set_transfer_hook},
+    instructions::timelock::{apply_cancel_compliance_change, apply_set_compliance_timelock},
+    state::*,
+};
+
+/// Hand sensitive owner actions over to an M-of-N multisig
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    /// Owner performing the operation
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Multisig PDA
+    #[account(
+        init,
+        payer = owner,
+        space = Multisig::LEN,
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_multisig(
+    ctx: Context<CreateMultisig>,
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+    proposal_ttl: i64,
+) -> Result<()> {
+    require!(proposal_ttl > 0, ERC3643Error::InvalidArgument);
+
+    let now = Clock::get()?.unix_timestamp;
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.mint = ctx.accounts.mint.key();
+    multisig.proposal_ttl = proposal_ttl;
+    multisig.proposal_count = 0;
+    multisig.created_at = now;
+    multisig.reserved = [0; 32];
+    multisig.set_approvers(approvers.clone(), threshold)?;
+
+    // From now on the guarded owner actions only run through proposals
+    let config = &mut ctx.accounts.config;
+    config.multisig = multisig.key();
+    config.updated_at = now;
+
+    emit!(MultisigCreated {
+        mint: multisig.mint,
+        multisig: multisig.key(),
+        approvers,
+        threshold,
+    });
+
+    msg!("Multisig created with threshold {}", threshold);
+
+    Ok(())
+}
+
+/// Propose an admin action
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    /// Approver creating the proposal, pays for the proposal account
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Multisig
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump,
+        constraint = multisig.is_approver(&proposer.key()) @ ERC3643Error::NotAnApprover
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// Proposal PDA
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::LEN,
+        seeds = [PROPOSAL_SEED, mint.key().as_ref(), multisig.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_proposal(ctx: Context<CreateProposal>, action: AdminAction) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let proposer = ctx.accounts.proposer.key();
+    let proposal = &mut ctx.accounts.proposal;
+    ctx.accounts
+        .multisig
+        .propose(proposal, ctx.accounts.mint.key(), proposer, action.clone(), now)?;
+
+    emit!(ProposalCreated {
+        mint: proposal.mint,
+        proposal: proposal.key(),
+        id: proposal.id,
+        proposer,
+        action,
+        expires_at: proposal.expires_at,
+    });
+
+    msg!("Proposal {} created by: {}", proposal.id, proposer);
+
+    Ok(())
+}
+
+/// Approve a proposal
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    /// Approver
+    pub approver: Signer<'info>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Multisig
+    #[account(
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump,
+        constraint = multisig.is_approver(&approver.key()) @ ERC3643Error::NotAnApprover
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// Proposal to approve
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, mint.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let proposal = &mut ctx.accounts.proposal;
+    let approver = ctx.accounts.approver.key();
+
+    multisig.approve(proposal, approver, Clock::get()?.unix_timestamp)?;
+
+    emit!(ProposalApproved {
+        proposal: proposal.key(),
+        approver,
+        approvals: proposal.approvals.len() as u8,
+    });
+
+    msg!("Proposal {} approved by: {}", proposal.id, approver);
+
+    Ok(())
+}
+
+/// Execute a proposal that reached the threshold
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    /// Approver executing the proposal, funds any metadata growth
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        constraint = config.multisig == multisig.key() @ ERC3643Error::MultisigRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint, updated by SetTransferHook and SetTokenInformation
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Multisig
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump,
+        constraint = multisig.is_approver(&executor.key()) @ ERC3643Error::NotAnApprover
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// Trusted issuers registry
+    #[account(
+        mut,
+        seeds = [TRUSTED_ISSUERS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub trusted_issuers_registry: Account<'info, TrustedIssuersRegistry>,
+
+    /// Claim topics registry
+    #[account(
+        mut,
+        seeds = [CLAIM_TOPICS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub claim_topics_registry: Account<'info, ClaimTopicsRegistry>,
+
+    /// Role registry
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Proposal to execute, closed back to the proposer
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PROPOSAL_SEED, mint.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Proposer receiving the proposal rent
+    /// CHECK: Must match the proposal's proposer
+    #[account(mut, address = proposal.proposer @ ERC3643Error::InvalidArgument)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Compliance registry, required by SetComplianceTimelock
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Option<Box<Account<'info, ComplianceRegistry>>>,
+
+    /// Queued change, required by CancelComplianceChange
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_CHANGE_SEED, mint.key().as_ref(), queued_change.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub queued_change: Option<Box<Account<'info, QueuedComplianceChange>>>,
+
+    /// Account that queued the change, receiving its rent
+    /// CHECK: Must match the queued change's queued_by
+    #[account(mut)]
+    pub queued_by: Option<UncheckedAccount<'info>>,
+
+    /// New transfer hook program, required by SetTransferHook
+    /// CHECK: Must be an executable program matching the proposal
+    #[account(executable)]
+    pub transfer_hook_program: Option<UncheckedAccount<'info>>,
+
+    /// Token program (Token-2022), required by SetTransferHook and SetTokenInformation
+    pub token_program: Option<Program<'info, Token2022>>,
+
+    /// System program, required by SetTokenInformation
+    pub system_program: Option<Program<'info, System>>,
+}
+
+pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+    let multisig_key = ctx.accounts.multisig.key();
+    let proposal = &ctx.accounts.proposal;
+
+    ctx.accounts
+        .multisig
+        .check_executable(proposal, Clock::get()?.unix_timestamp)?;
+
+    let mint = ctx.accounts.mint.key();
+    let config = &mut ctx.accounts.config;
+    match proposal.action.clone() {
+        AdminAction::AddAgent { agent, role } => {
+            apply_add_agent(&mut ctx.accounts.role_registry, agent, role, multisig_key)?
+        }
+        AdminAction::RemoveAgent { agent, role } => {
+            apply_remove_agent(&mut ctx.accounts.role_registry, agent, role, multisig_key)?
+        }
+        AdminAction::AddTrustedIssuer { issuer, claim_topics } => {
+            apply_add_trusted_issuer(&mut ctx.accounts.trusted_issuers_registry, issuer, claim_topics)?
+        }
+        AdminAction::RemoveTrustedIssuer { issuer } => {
+            apply_remove_trusted_issuer(&mut ctx.accounts.trusted_issuers_registry, issuer)?
+        }
+        AdminAction::AddClaimTopic { topic } => {
+            apply_add_claim_topic(&mut ctx.accounts.claim_topics_registry, topic)?
+        }
+        AdminAction::RemoveClaimTopic { topic } => {
+            apply_remove_claim_topic(&mut ctx.accounts.claim_topics_registry, topic)?
+        }
+        AdminAction::GrantRole { member, role } => {
+            apply_grant_role(&mut ctx.accounts.role_registry, mint, member, role, multisig_key)?
+        }
+        AdminAction::RevokeRole { member, role } => {
+            apply_revoke_role(&mut ctx.accounts.role_registry, mint, member, role, multisig_key)?
+        }
+        AdminAction::SetComplianceManager { compliance_manager } => {
+            apply_set_compliance_manager(config, &mut ctx.accounts.role_registry, compliance_manager)?
+        }
+        AdminAction::SetMaxSupply { max_supply } => {
+            apply_set_max_supply(config, mint, ctx.accounts.mint.supply, max_supply)?
+        }
+        AdminAction::SetPauseStatus { paused } => apply_set_pause_status(config, paused, multisig_key)?,
+        AdminAction::TransferOwnership { new_owner } => apply_transfer_ownership(config, new_owner)?,
+        AdminAction::CancelOwnershipTransfer => apply_cancel_ownership_transfer(config)?,
+        AdminAction::SetTransferHook { hook_program } => {
+            let hook = ctx.accounts.transfer_hook_program.as_ref().ok_or(ERC3643Error::InvalidArgument)?;
+            require_keys_eq!(hook.key(), hook_program, ERC3643Error::InvalidArgument);
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ERC3643Error::InvalidArgument)?;
+            apply_set_transfer_hook(
+                config,
+                ctx.accounts.mint.to_account_info(),
+                token_program.to_account_info(),
+                ctx.bumps.config,
+                hook_program,
+            )?
+        }
+        AdminAction::SetTokenInformation { name, symbol, onchain_id } => {
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ERC3643Error::InvalidArgument)?;
+            let system_program = ctx.accounts.system_program.as_ref().ok_or(ERC3643Error::InvalidArgument)?;
+            let accounts = TokenMetadataAccounts {
+                mint: &ctx.accounts.mint,
+                token_program: token_program.to_account_info(),
+                system_program: system_program.to_account_info(),
+                payer: ctx.accounts.executor.to_account_info(),
+            };
+            apply_set_token_information(config, accounts, ctx.bumps.config, name, symbol, onchain_id)?
+        }
+        AdminAction::SetEnforcementMode { mode } => apply_set_enforcement_mode(config, mode, multisig_key)?,
+        AdminAction::SetComplianceTimelock { delay } => {
+            let compliance = ctx.accounts.compliance_registry.as_mut().ok_or(ERC3643Error::InvalidArgument)?;
+            apply_set_compliance_timelock(compliance, delay)?
+        }
+        AdminAction::CancelComplianceChange { id } => {
+            let change = ctx.accounts.queued_change.as_ref().ok_or(ERC3643Error::InvalidArgument)?;
+            let queued_by = ctx.accounts.queued_by.as_ref().ok_or(ERC3643Error::InvalidArgument)?;
+            require!(change.id == id, ERC3643Error::InvalidArgument);
+            require_keys_eq!(queued_by.key(), change.queued_by, ERC3643Error::InvalidArgument);
+            apply_cancel_compliance_change(change)?;
+            change.close(queued_by.to_account_info())?;
+        }
+        AdminAction::SetApprovers { approvers, threshold } => {
+            ctx.accounts.multisig.set_approvers(approvers.clone(), threshold)?;
+            emit!(ApproversUpdated {
+                mint: ctx.accounts.mint.key(),
+                approvers,
+                threshold,
+            });
+        }
+    }
+
+    emit!(ProposalExecuted {
+        proposal: proposal.key(),
+        id: proposal.id,
+        executed_by: ctx.accounts.executor.key(),
+    });
+
+    msg!("Proposal {} executed", proposal.id);
+
+    Ok(())
+}
+
+/// Cancel a proposal, or close it once expired
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    /// Proposer, or anyone once the proposal expired
+    pub authority: Signer<'info>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Proposal to cancel, closed back to the proposer
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PROPOSAL_SEED, mint.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Proposer receiving the proposal rent
+    /// CHECK: Must match the proposal's proposer
+    #[account(mut, address = proposal.proposer @ ERC3643Error::InvalidArgument)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    let authority = ctx.accounts.authority.key();
+
+    require!(
+        proposal.can_cancel(&authority, Clock::get()?.unix_timestamp),
+        ERC3643Error::ProposalNotExpired
+    );
+
+    emit!(ProposalCancelled {
+        proposal: proposal.key(),
+        id: proposal.id,
+        cancelled_by: authority,
+    });
+
+    msg!("Proposal {} cancelled by: {}", proposal.id, authority);
+
+    Ok(())
+}
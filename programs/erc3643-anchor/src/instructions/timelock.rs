@@ -0,0 +1,250 @@
+use anchor_lang::prelude::*;
+use crate::{
+    error::ERC3643Error,
+    events::*,
+    instructions::compliance_rules::apply_compliance_action,
+    state::*,
+};
+
+/// Set the compliance timelock delay
+#[derive(Accounts)]
+pub struct SetComplianceTimelock<'info> {
+    /// Owner performing the operation
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+}
+
+pub fn set_compliance_timelock(ctx: Context<SetComplianceTimelock>, delay: i64) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_set_compliance_timelock(&mut ctx.accounts.compliance_registry, delay)
+}
+
+pub(crate) fn apply_set_compliance_timelock(compliance: &mut ComplianceRegistry, delay: i64) -> Result<()> {
+    // Shortening the delay is itself a change holders get notice of
+    require!(delay >= compliance.timelock_delay, ERC3643Error::TimelockRequired);
+
+    apply_compliance_action(compliance, ComplianceAction::SetTimelockDelay { delay }, 0)
+}
+
+/// Queue a compliance change behind the timelock
+#[derive(Accounts)]
+pub struct QueueComplianceChange<'info> {
+    /// Owner or compliance manager queueing the change, pays for the queued change
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
+    /// Queued change PDA
+    #[account(
+        init,
+        payer = authority,
+        space = QueuedComplianceChange::LEN,
+        seeds = [
+            COMPLIANCE_CHANGE_SEED,
+            mint.key().as_ref(),
+            compliance_registry.queued_change_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub queued_change: Account<'info, QueuedComplianceChange>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn queue_compliance_change(
+    ctx: Context<QueueComplianceChange>,
+    action: ComplianceAction,
+) -> Result<()> {
+    let compliance = &mut ctx.accounts.compliance_registry;
+    let authority = ctx.accounts.authority.key();
+
+    if let ComplianceAction::SetTimelockDelay { delay } = action {
+        require!(ctx.accounts.config.is_owner(&authority), ERC3643Error::OwnerRoleRequired);
+        require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+        require!(delay >= 0, ERC3643Error::InvalidComplianceConfiguration);
+    }
+
+    let change = &mut ctx.accounts.queued_change;
+    change.queue(
+        compliance,
+        ctx.accounts.mint.key(),
+        action.clone(),
+        authority,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    emit!(ComplianceChangeQueued {
+        mint: change.mint,
+        change: change.key(),
+        id: change.id,
+        action,
+        eta: change.eta,
+    });
+
+    msg!("Compliance change {} queued, executable at {}", change.id, change.eta);
+
+    Ok(())
+}
+
+/// Execute a queued compliance change once its eta passed
+#[derive(Accounts)]
+pub struct ExecuteComplianceChange<'info> {
+    /// Owner or compliance manager executing the change
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::ComplianceManagerRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
+    /// Queued change to execute, closed back to whoever queued it
+    #[account(
+        mut,
+        close = queued_by,
+        seeds = [COMPLIANCE_CHANGE_SEED, mint.key().as_ref(), queued_change.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub queued_change: Account<'info, QueuedComplianceChange>,
+
+    /// Account that queued the change, receiving the rent
+    /// CHECK: Must match the queued change's queued_by
+    #[account(mut, address = queued_change.queued_by @ ERC3643Error::InvalidArgument)]
+    pub queued_by: UncheckedAccount<'info>,
+}
+
+pub fn execute_compliance_change(
+    ctx: Context<ExecuteComplianceChange>,
+    current_investors: u32,
+) -> Result<()> {
+    let change = &ctx.accounts.queued_change;
+
+    require!(change.is_ready(Clock::get()?.unix_timestamp), ERC3643Error::TimelockNotElapsed);
+
+    apply_compliance_action(&mut ctx.accounts.compliance_registry, change.action.clone(), current_investors)?;
+
+    emit!(ComplianceChangeExecuted {
+        mint: change.mint,
+        change: change.key(),
+        id: change.id,
+    });
+
+    msg!("Compliance change {} executed", change.id);
+
+    Ok(())
+}
+
+/// Cancel a queued compliance change
+#[derive(Accounts)]
+pub struct CancelComplianceChange<'info> {
+    /// Owner performing the operation
+    pub owner: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = owner @ ERC3643Error::OwnerRoleRequired
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint
+    pub mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+
+    /// Queued change to cancel, closed back to whoever queued it
+    #[account(
+        mut,
+        close = queued_by,
+        seeds = [COMPLIANCE_CHANGE_SEED, mint.key().as_ref(), queued_change.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub queued_change: Account<'info, QueuedComplianceChange>,
+
+    /// Account that queued the change, receiving the rent
+    /// CHECK: Must match the queued change's queued_by
+    #[account(mut, address = queued_change.queued_by @ ERC3643Error::InvalidArgument)]
+    pub queued_by: UncheckedAccount<'info>,
+}
+
+pub fn cancel_compliance_change(ctx: Context<CancelComplianceChange>) -> Result<()> {
+    require!(!ctx.accounts.config.has_multisig(), ERC3643Error::MultisigRequired);
+    apply_cancel_compliance_change(&ctx.accounts.queued_change)
+}
+
+/// Emit the cancellation; the caller closes the queued change
+pub(crate) fn apply_cancel_compliance_change(change: &Account<QueuedComplianceChange>) -> Result<()> {
+    emit!(ComplianceChangeCancelled {
+        mint: change.mint,
+        change: change.key(),
+        id: change.id,
+    });
+
+    msg!("Compliance change {} cancelled", change.id);
+
+    Ok(())
+}
@@ -1,11 +1,20 @@
-use crate::{error::ERC3643Error, events::*, state::*};
+use crate::{
+    error::ERC3643Error,
+    events::*,
+    modules::{check_modules, hook_module_accounts, notify_modules, ModuleCall, ModuleHook},
+    state::*,
+    utils::{thaw_if_frozen, transfer_as_permanent_delegate},
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token_2022::{self, Token2022},
+    token_2022::Token2022,
     token_interface::{burn, mint_to, Burn, Mint, MintTo, TokenAccount},
 };
 
 /// Mint tokens to a verified address
+///
+/// Accounts of the custom compliance modules must be passed as remaining
+/// accounts.
 #[derive(Accounts)]
 pub struct MintTokens<'info> {
     /// Agent or owner performing the mint
@@ -16,8 +25,7 @@ pub struct MintTokens<'info> {
     #[account(
         mut,
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
@@ -25,13 +33,21 @@ pub struct MintTokens<'info> {
     #[account(mut)]
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::SupplyAgent) @ ERC3643Error::SupplyAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Destination token account
     #[account(mut)]
     pub to_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Identity registry for the recipient
     #[account(
-        seeds = [IDENTITY_SEED, to_token_account.owner.as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), to_token_account.owner.as_ref()],
         bump,
         constraint = identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
     )]
@@ -39,6 +55,7 @@ pub struct MintTokens<'info> {
 
     /// Compliance registry
     #[account(
+        mut,
         seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
         bump
     )]
@@ -48,12 +65,15 @@ pub struct MintTokens<'info> {
     pub token_program: Program<'info, Token2022>,
 }
 
-pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+pub fn mint_tokens<'info>(
+    ctx: Context<'_, '_, '_, 'info, MintTokens<'info>>,
+    amount: u64,
+) -> Result<()> {
     let config = &mut ctx.accounts.config;
     let mint = &ctx.accounts.mint;
     let to_token_account = &ctx.accounts.to_token_account;
     let identity = &ctx.accounts.identity_registry;
-    let compliance = &ctx.accounts.compliance_registry;
+    let compliance = &mut ctx.accounts.compliance_registry;
 
     // Validate inputs
     validate_amount(amount)?;
@@ -61,17 +81,34 @@ pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
     // Check if token is paused
     require!(!config.paused, ERC3643Error::TokenPaused);
 
+    // Check the supply cap against the live mint supply
+    let new_supply = mint
+        .supply
+        .checked_add(amount)
+        .ok_or(ERC3643Error::ArithmeticOverflow)?;
+    if let Some(max_supply) = config.max_supply {
+        require!(new_supply <= max_supply, ERC3643Error::MaxSupplyExceeded);
+    }
+
     // Check compliance for minting (from zero address to recipient)
     let compliance_result = compliance.check_transfer_compliance(
-        &Pubkey::default(), // from (mint operation)
-        &to_token_account.owner,
-        amount,
-        0, // from_balance (minting from zero)
-        to_token_account.amount,
-        0, // from_country (not applicable for minting)
-        identity.country,
+        &TransferCheck {
+            from: Pubkey::default(), // mint operation
+            to: to_token_account.owner,
+            amount,
+            from_balance: 0, // minting from zero
+            to_balance: to_token_account.amount,
+            supply: new_supply,
+            from_country: 0, // not applicable for minting
+            to_country: identity.country,
+        },
+        None, // no sender volume when minting
     )?;
 
+    require!(
+        !compliance_result.requires_approval,
+        ERC3643Error::TransferRequiresApproval
+    );
     require!(
         compliance_result.allowed,
         ERC3643Error::TransferNotCompliant
@@ -82,6 +119,29 @@ pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
     let mint_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
     let signer_seeds = &[&mint_seeds[..]];
 
+    let modules = compliance.custom_modules();
+    let rejected_by = check_modules(
+        &modules,
+        ctx.remaining_accounts,
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: Pubkey::default(),
+            to: to_token_account.owner,
+            amount,
+        },
+    )?;
+    require!(rejected_by.is_none(), ERC3643Error::TransferNotCompliant);
+
+    // New token accounts start frozen; the verified recipient can be thawed
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        to_token_account,
+        &mint.to_account_info(),
+        &config.to_account_info(),
+        signer_seeds,
+    )?;
+
     // Mint tokens
     let cpi_accounts = MintTo {
         mint: mint.to_account_info(),
@@ -93,6 +153,25 @@ pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
 
     mint_to(cpi_ctx, amount)?;
 
+    compliance.record_balance_change(
+        identity.country,
+        to_token_account.amount,
+        to_token_account.amount.saturating_add(amount),
+    )?;
+
+    notify_modules(
+        ModuleHook::Mint,
+        &modules,
+        ctx.remaining_accounts,
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: Pubkey::default(),
+            to: to_token_account.owner,
+            amount,
+        },
+    )?;
+
     // Update total supply
     let current_supply = config.total_supply;
     config.update_total_supply(current_supply + amount)?;
@@ -103,7 +182,73 @@ pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
     Ok(())
 }
 
+/// Thaw a verified holder's token account
+#[derive(Accounts)]
+pub struct ThawTokenAccount<'info> {
+    /// Agent or owner performing the thaw
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint account
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::FreezeAgent) @ ERC3643Error::FreezeAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// Token account to thaw
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ ERC3643Error::InvalidArgument
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Identity registry for the holder (must be verified)
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), token_account.owner.as_ref()],
+        bump,
+        constraint = identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
+    )]
+    pub identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Token program
+    pub token_program: Program<'info, Token2022>,
+}
+
+pub fn thaw_token_account(ctx: Context<ThawTokenAccount>) -> Result<()> {
+    let mint_key = ctx.accounts.mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.token_account,
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.config.to_account_info(),
+        signer_seeds,
+    )?;
+
+    msg!("Token account thawed: {}", ctx.accounts.token_account.key());
+    msg!("Holder: {}", ctx.accounts.token_account.owner);
+
+    Ok(())
+}
+
 /// Burn tokens from an address
+///
+/// Accounts of the custom compliance modules must be passed as remaining
+/// accounts.
 #[derive(Accounts)]
 pub struct BurnTokens<'info> {
     /// Agent or owner performing the burn
@@ -114,8 +259,7 @@ pub struct BurnTokens<'info> {
     #[account(
         mut,
         seeds = [CONFIG_SEED, mint.key().as_ref()],
-        bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired
+        bump
     )]
     pub config: Account<'info, TokenConfig>,
 
@@ -123,8 +267,19 @@ pub struct BurnTokens<'info> {
     #[account(mut)]
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::SupplyAgent) @ ERC3643Error::SupplyAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Source token account
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument
+    )]
     pub from_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Frozen account info (if exists)
@@ -135,11 +290,29 @@ pub struct BurnTokens<'info> {
     )]
     pub frozen_account: Option<Account<'info, FrozenAccount>>,
 
+    /// Identity registry for the holder (if registered), for investor counts
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), from_token_account.owner.as_ref()],
+        bump
+    )]
+    pub identity_registry: Option<Account<'info, IdentityRegistry>>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
     /// Token program
     pub token_program: Program<'info, Token2022>,
 }
 
-pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
+pub fn burn_tokens<'info>(
+    ctx: Context<'_, '_, '_, 'info, BurnTokens<'info>>,
+    amount: u64,
+) -> Result<()> {
     let config = &mut ctx.accounts.config;
     let mint = &ctx.accounts.mint;
     let from_token_account = &ctx.accounts.from_token_account;
@@ -159,7 +332,7 @@ pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
 
     // Handle frozen tokens if necessary
     if let Some(frozen_info) = frozen_account {
-        let free_balance = from_token_account.amount - frozen_info.frozen_amount;
+        let free_balance = from_token_account.amount.saturating_sub(frozen_info.frozen_amount);
 
         if amount > free_balance {
             // Need to unfreeze some tokens
@@ -173,20 +346,50 @@ pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
         }
     }
 
-    // Burn tokens - agent acts as authority for forced burn
+    // Burn tokens - config PDA acts as permanent delegate for forced burn
+    let mint_key = mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
     let cpi_accounts = Burn {
         mint: mint.to_account_info(),
         from: from_token_account.to_account_info(),
-        authority: ctx.accounts.agent.to_account_info(),  // Agent acts as authority. Need to check if agent has admin rights
+        authority: config.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
     burn(cpi_ctx, amount)?;
 
+    let country = ctx.accounts.identity_registry.as_ref().map_or(0, |identity| identity.country);
+    let new_balance = from_token_account
+        .amount
+        .checked_sub(amount)
+        .ok_or(ERC3643Error::InsufficientBalance)?;
+    ctx.accounts.compliance_registry.record_balance_change(
+        country,
+        from_token_account.amount,
+        new_balance,
+    )?;
+
+    notify_modules(
+        ModuleHook::Burn,
+        &ctx.accounts.compliance_registry.custom_modules(),
+        ctx.remaining_accounts,
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: from_token_account.owner,
+            to: Pubkey::default(),
+            amount,
+        },
+    )?;
+
     // Update total supply
-    let current_supply = config.total_supply;
-    config.update_total_supply(current_supply - amount)?;
+    let new_supply = config
+        .total_supply
+        .checked_sub(amount)
+        .ok_or(ERC3643Error::ArithmeticUnderflow)?;
+    config.update_total_supply(new_supply)?;
 
     msg!("Burned {} tokens from {}", amount, from_token_account.owner);
     msg!("New total supply: {}", config.total_supply);
@@ -195,6 +398,10 @@ pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
 }
 
 /// Forced transfer (agent only)
+///
+/// Any extra accounts required by the mint's transfer hook must be passed as
+/// remaining accounts. Custom compliance modules are notified of the transfer
+/// through the module accounts among them.
 #[derive(Accounts)]
 pub struct ForcedTransfer<'info> {
     /// Agent performing the forced transfer
@@ -205,7 +412,6 @@ pub struct ForcedTransfer<'info> {
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
         bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired,
         constraint = config.allow_forced_transfers @ ERC3643Error::Unauthorized
     )]
     pub config: Account<'info, TokenConfig>,
@@ -213,22 +419,51 @@ pub struct ForcedTransfer<'info> {
     /// The mint account
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::TransferAgent) @ ERC3643Error::TransferAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Source token account
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument
+    )]
     pub from_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Destination token account
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument
+    )]
     pub to_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Identity registry for the recipient (must be verified)
     #[account(
-        seeds = [IDENTITY_SEED, to_token_account.owner.as_ref()],
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), to_token_account.owner.as_ref()],
         bump,
         constraint = to_identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
     )]
     pub to_identity_registry: Account<'info, IdentityRegistry>,
 
+    /// Identity registry for the sender (if registered), for investor counts
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), from_token_account.owner.as_ref()],
+        bump
+    )]
+    pub from_identity_registry: Option<Account<'info, IdentityRegistry>>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
     /// Frozen account info for source (if exists)
     #[account(
         mut,
@@ -241,10 +476,10 @@ pub struct ForcedTransfer<'info> {
     pub token_program: Program<'info, Token2022>,
 }
 
-pub fn forced_transfer(
-    ctx: Context<ForcedTransfer>,
-    _from: Pubkey,
-    _to: Pubkey,
+pub fn forced_transfer<'info>(
+    ctx: Context<'_, '_, '_, 'info, ForcedTransfer<'info>>,
+    from: Pubkey,
+    to: Pubkey,
     amount: u64,
 ) -> Result<()> {
     let config = &ctx.accounts.config;
@@ -254,6 +489,8 @@ pub fn forced_transfer(
 
     // Validate inputs
     validate_amount(amount)?;
+    require_keys_eq!(from_token_account.owner, from, ERC3643Error::InvalidArgument);
+    require_keys_eq!(to_token_account.owner, to, ERC3643Error::InvalidArgument);
 
     // Check if token is paused
     require!(!config.paused, ERC3643Error::TokenPaused);
@@ -266,7 +503,7 @@ pub fn forced_transfer(
 
     // Handle frozen tokens if necessary
     if let Some(frozen_info) = from_frozen_account {
-        let free_balance = from_token_account.amount - frozen_info.frozen_amount;
+        let free_balance = from_token_account.amount.saturating_sub(frozen_info.frozen_amount);
 
         if amount > free_balance {
             // Unfreeze tokens as needed for forced transfer
@@ -280,12 +517,64 @@ pub fn forced_transfer(
         }
     }
 
-    // In a real implementation, we would perform the actual token transfer here
-    // This would involve either:
-    // 1. Using Token-2022 transfer with proper authority
-    // 2. Using a program-controlled transfer mechanism
+    // Move the tokens with the config PDA acting as permanent delegate
+    let mint = &ctx.accounts.mint;
+    let mint_key = mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        to_token_account,
+        &mint.to_account_info(),
+        &config.to_account_info(),
+        signer_seeds,
+    )?;
+
+    transfer_as_permanent_delegate(
+        &ctx.accounts.token_program.to_account_info(),
+        &from_token_account.to_account_info(),
+        &mint.to_account_info(),
+        &to_token_account.to_account_info(),
+        &config.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+
+    // The hook skips permanent delegate transfers, so count investors here
+    let from_country = ctx.accounts.from_identity_registry.as_ref().map_or(0, |identity| identity.country);
+    let to_country = ctx.accounts.to_identity_registry.country;
+    let from_new_balance = from_token_account
+        .amount
+        .checked_sub(amount)
+        .ok_or(ERC3643Error::InsufficientBalance)?;
+    let compliance = &mut ctx.accounts.compliance_registry;
+    compliance.record_balance_change(
+        from_country,
+        from_token_account.amount,
+        from_new_balance,
+    )?;
+    compliance.record_balance_change(
+        to_country,
+        to_token_account.amount,
+        to_token_account.amount.saturating_add(amount),
+    )?;
+
+    notify_modules(
+        ModuleHook::Transfer,
+        &compliance.custom_modules(),
+        hook_module_accounts(ctx.remaining_accounts, &mint_key),
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: from_token_account.owner,
+            to: to_token_account.owner,
+            amount,
+        },
+    )?;
 
-    // For now, we'll emit the event to indicate the forced transfer
     msg!(
         "Forced transfer: {} tokens from {} to {}",
         amount,
@@ -298,8 +587,16 @@ pub fn forced_transfer(
 }
 
 /// Recovery address (agent only)
+///
+/// Any extra accounts required by the mint's transfer hook must be passed as
+/// remaining accounts. Custom compliance modules are notified of the sweep
+/// through the module accounts among them.
 #[derive(Accounts)]
+#[instruction(lost_wallet: Pubkey, new_wallet: Pubkey)]
 pub struct RecoveryAddress<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     /// Agent performing the recovery
     #[account(mut)]
     pub agent: Signer<'info>,
@@ -308,7 +605,6 @@ pub struct RecoveryAddress<'info> {
     #[account(
         seeds = [CONFIG_SEED, mint.key().as_ref()],
         bump,
-        constraint = config.has_agent_or_owner_role(&agent.key()) @ ERC3643Error::AgentRoleRequired,
         constraint = config.enable_recovery @ ERC3643Error::RecoveryNotAllowed
     )]
     pub config: Account<'info, TokenConfig>,
@@ -316,48 +612,135 @@ pub struct RecoveryAddress<'info> {
     /// The mint account
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &agent.key(), Role::RecoveryAgent) @ ERC3643Error::RecoveryAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     /// Lost wallet token account
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = lost_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument
+    )]
     pub lost_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// New wallet token account
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = new_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument
+    )]
     pub new_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// Identity registry for the investor (must match both wallets)
+    /// Identity registry of the lost wallet, closed once migrated
     #[account(
-        seeds = [IDENTITY_SEED, investor_identity.key().as_ref()],
+        mut,
+        close = agent,
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), lost_wallet.as_ref()],
         bump,
-        constraint = identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
+        constraint = lost_identity_registry.is_verified @ ERC3643Error::IdentityNotVerified,
+        constraint = lost_identity_registry.identity == investor_identity.key() @ ERC3643Error::InvalidRecoveryIdentity
     )]
-    pub identity_registry: Account<'info, IdentityRegistry>,
+    pub lost_identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Identity registry for the new wallet
+    #[account(
+        init,
+        payer = payer,
+        space = IdentityRegistry::LEN,
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), new_wallet.as_ref()],
+        bump
+    )]
+    pub new_identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Shared identity storage of the lost wallet, closed once no token is bound to it
+    #[account(
+        mut,
+        seeds = [IDENTITY_STORAGE_SEED, lost_wallet.as_ref()],
+        bump,
+        constraint = lost_identity_registry.storage == lost_identity_storage.key() @ ERC3643Error::IdentityStorageMismatch
+    )]
+    pub lost_identity_storage: Account<'info, IdentityStorage>,
+
+    /// Shared identity storage of the new wallet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = IdentityStorage::LEN,
+        seeds = [IDENTITY_STORAGE_SEED, new_wallet.as_ref()],
+        bump
+    )]
+    pub new_identity_storage: Account<'info, IdentityStorage>,
+
+    /// Transfer volume counter of the lost wallet, closed once carried over
+    #[account(
+        mut,
+        close = agent,
+        seeds = [TRANSFER_COUNTER_SEED, mint.key().as_ref(), lost_wallet.as_ref()],
+        bump
+    )]
+    pub lost_transfer_counter: Account<'info, TransferCounter>,
+
+    /// Transfer volume counter for the new wallet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TransferCounter::LEN,
+        seeds = [TRANSFER_COUNTER_SEED, mint.key().as_ref(), new_wallet.as_ref()],
+        bump
+    )]
+    pub new_transfer_counter: Account<'info, TransferCounter>,
 
     /// Investor's onchain identity
-    /// CHECK: This should be validated against the identity registry
+    /// CHECK: Validated against the lost wallet's identity registry
     pub investor_identity: UncheckedAccount<'info>,
 
     /// Frozen account info for lost wallet (if exists)
     #[account(
         mut,
-        seeds = [b"frozen", mint.key().as_ref(), lost_token_account.owner.as_ref()],
+        seeds = [b"frozen", mint.key().as_ref(), lost_wallet.as_ref()],
         bump
     )]
     pub lost_frozen_account: Option<Account<'info, FrozenAccount>>,
 
+    /// Frozen account info for new wallet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = FrozenAccount::LEN,
+        seeds = [b"frozen", mint.key().as_ref(), new_wallet.as_ref()],
+        bump
+    )]
+    pub new_frozen_account: Account<'info, FrozenAccount>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
     /// Token program
     pub token_program: Program<'info, Token2022>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
 }
 
-pub fn recovery_address(
-    ctx: Context<RecoveryAddress>,
+pub fn recovery_address<'info>(
+    ctx: Context<'_, '_, '_, 'info, RecoveryAddress<'info>>,
     lost_wallet: Pubkey,
     new_wallet: Pubkey,
     investor_identity: Pubkey,
 ) -> Result<()> {
     let config = &ctx.accounts.config;
+    let mint = &ctx.accounts.mint;
     let lost_token_account = &ctx.accounts.lost_token_account;
     let new_token_account = &ctx.accounts.new_token_account;
-    let lost_frozen_account = &mut ctx.accounts.lost_frozen_account;
+    let agent = &ctx.accounts.agent;
 
     // Validate inputs
     validate_pubkey(&lost_wallet)?;
@@ -382,22 +765,168 @@ pub fn recovery_address(
 
     let recovery_amount = lost_token_account.amount;
 
-    // Handle frozen tokens if any
-    if let Some(frozen_info) = lost_frozen_account {
-        if frozen_info.frozen_amount > 0 {
-            // Unfreeze all tokens for recovery
-            let frozen_amount = frozen_info.frozen_amount;
-            frozen_info.unfreeze_tokens(frozen_amount, ctx.accounts.agent.key())?;
+    // Sweep the full balance with the config PDA acting as permanent delegate
+    if recovery_amount > 0 {
+        let mint_key = mint.key();
+        let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+        let signer_seeds = &[&config_seeds[..]];
+
+        thaw_if_frozen(
+            &ctx.accounts.token_program.to_account_info(),
+            new_token_account,
+            &mint.to_account_info(),
+            &config.to_account_info(),
+            signer_seeds,
+        )?;
+
+        transfer_as_permanent_delegate(
+            &ctx.accounts.token_program.to_account_info(),
+            &lost_token_account.to_account_info(),
+            &mint.to_account_info(),
+            &new_token_account.to_account_info(),
+            &config.to_account_info(),
+            ctx.remaining_accounts,
+            recovery_amount,
+            mint.decimals,
+            signer_seeds,
+        )?;
+
+        let country = ctx.accounts.lost_identity_registry.country;
+        let compliance = &mut ctx.accounts.compliance_registry;
+        compliance.record_balance_change(country, recovery_amount, 0)?;
+        compliance.record_balance_change(
+            country,
+            new_token_account.amount,
+            new_token_account.amount.saturating_add(recovery_amount),
+        )?;
+
+        notify_modules(
+            ModuleHook::Transfer,
+            &compliance.custom_modules(),
+            hook_module_accounts(ctx.remaining_accounts, &mint_key),
+            &ModuleCall {
+                config: &config.to_account_info(),
+                mint: &mint.to_account_info(),
+                from: lost_wallet,
+                to: new_wallet,
+                amount: recovery_amount,
+            },
+        )?;
+    }
+
+    // Carry the freeze state over to the new wallet
+    let new_frozen = &mut ctx.accounts.new_frozen_account;
+    if new_frozen.user == Pubkey::default() {
+        new_frozen.user = new_wallet;
+        new_frozen.mint = mint.key();
+        new_frozen.is_frozen = false;
+        new_frozen.frozen_amount = 0;
+        new_frozen.frozen_at = 0;
+        new_frozen.frozen_by = Pubkey::default();
+        new_frozen.reserved = [0; 64];
+    }
+
+    if let Some(lost_frozen) = &mut ctx.accounts.lost_frozen_account {
+        if lost_frozen.is_address_frozen() {
+            new_frozen.set_frozen(true, agent.key())?;
+
+            emit!(AddressFrozen {
+                user_address: new_wallet,
+                is_frozen: true,
+                agent: agent.key(),
+            });
+        }
+
+        let frozen_amount = lost_frozen.frozen_amount;
+        if frozen_amount > 0 {
+            lost_frozen.unfreeze_tokens(frozen_amount, agent.key())?;
+            new_frozen.freeze_tokens(frozen_amount, agent.key())?;
 
             emit!(TokensUnfrozen {
                 user_address: lost_wallet,
                 amount: frozen_amount,
             });
+            emit!(TokensFrozen {
+                user_address: new_wallet,
+                amount: frozen_amount,
+            });
         }
     }
 
-    // In a real implementation, we would perform the actual token transfer here
-    // This would involve transferring all tokens from lost_wallet to new_wallet
+    // Re-point the identity to the new wallet
+    let lost_identity = &ctx.accounts.lost_identity_registry;
+    let new_identity = &mut ctx.accounts.new_identity_registry;
+    let lost_storage = &mut ctx.accounts.lost_identity_storage;
+    let new_storage = &mut ctx.accounts.new_identity_storage;
+    let now = Clock::get()?.unix_timestamp;
+
+    if new_storage.user == Pubkey::default() {
+        new_storage.user = new_wallet;
+        new_storage.identity = lost_storage.identity;
+        new_storage.country = lost_storage.country;
+        new_storage.onchain_id = lost_storage.onchain_id;
+        new_storage.bound_registries = 0;
+        new_storage.created_at = now;
+        new_storage.updated_at = now;
+        new_storage.created_by = agent.key();
+        new_storage.reserved = [0; 32];
+    } else {
+        // The new wallet may already be in use by another token, but only
+        // for the same investor identity
+        require!(
+            new_storage.identity == lost_identity.identity,
+            ERC3643Error::InvalidRecoveryIdentity
+        );
+    }
+
+    new_identity.user = new_wallet;
+    new_identity.is_verified = lost_identity.is_verified;
+    new_identity.country = lost_identity.country;
+    new_identity.onchain_id = lost_identity.onchain_id;
+    new_identity.claims_count = lost_identity.claims_count;
+    new_identity.required_claims_met = lost_identity.required_claims_met;
+    new_identity.registered_at = now;
+    new_identity.last_verified_at = lost_identity.last_verified_at;
+    new_identity.updated_at = now;
+    new_identity.registered_by = agent.key();
+    new_identity.identity = lost_identity.identity;
+    new_identity.mint = mint.key();
+    new_identity.storage = new_storage.key();
+    new_identity.reserved = [0; 32];
+
+    lost_storage.unbind()?;
+    new_storage.bind()?;
+
+    // Carry the transfer limit windows over so recovery does not reset them
+    let new_counter = &mut ctx.accounts.new_transfer_counter;
+    new_counter.initialize_if_new(mint.key(), new_wallet);
+    new_counter.carry_over(&ctx.accounts.lost_transfer_counter);
+
+    emit!(IdentityRegistryUnbound {
+        identity_storage: lost_storage.key(),
+        identity_registry: lost_identity.key(),
+        mint: mint.key(),
+    });
+
+    emit!(IdentityRegistryBound {
+        identity_storage: new_storage.key(),
+        identity_registry: new_identity.key(),
+        mint: mint.key(),
+    });
+
+    if lost_storage.bound_registries == 0 {
+        lost_storage.close(agent.to_account_info())?;
+    }
+
+    emit!(IdentityRemoved {
+        investor_address: lost_wallet,
+        identity: lost_identity.key(),
+    });
+
+    emit!(IdentityRegistered {
+        investor_address: new_wallet,
+        identity: new_identity.key(),
+    });
 
     emit!(RecoverySuccess {
         lost_wallet,
@@ -412,7 +941,7 @@ pub fn recovery_address(
         new_wallet
     );
     msg!("Investor identity: {}", investor_identity);
-    msg!("Performed by agent: {}", ctx.accounts.agent.key());
+    msg!("Performed by agent: {}", agent.key());
 
     Ok(())
 }
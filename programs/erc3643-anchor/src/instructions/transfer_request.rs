@@ -0,0 +1,665 @@
+use crate::{
+    error::ERC3643Error,
+    events::*,
+    modules::{check_modules, hook_module_accounts, notify_modules, ModuleCall, ModuleHook},
+    state::*,
+    utils::{thaw_if_frozen, transfer_as_permanent_delegate},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount},
+};
+
+/// Escrow tokens for a transfer that needs issuer approval
+///
+/// The tokens move into the config PDA's token account with the config
+/// acting as permanent delegate. The transfer hook's extra accounts must be
+/// passed as remaining accounts; custom compliance modules check the transfer
+/// and are notified of the escrow through the module accounts among them.
+#[derive(Accounts)]
+#[instruction(request_id: u64, to: Pubkey)]
+pub struct RequestTransfer<'info> {
+    /// Holder escrowing the tokens
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint account
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
+    /// Holder token account
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument,
+        constraint = from_token_account.owner == holder.key() @ ERC3643Error::InvalidArgument
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Identity registry for the holder (must be verified)
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), holder.key().as_ref()],
+        bump,
+        constraint = from_identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
+    )]
+    pub from_identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Identity registry for the recipient (must be verified)
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), to.as_ref()],
+        bump,
+        constraint = to_identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
+    )]
+    pub to_identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Recipient token account
+    #[account(
+        constraint = to_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument,
+        constraint = to_token_account.owner == to @ ERC3643Error::InvalidArgument
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Frozen account info for the holder (if exists)
+    #[account(
+        seeds = [b"frozen", mint.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub from_frozen_account: Option<Account<'info, FrozenAccount>>,
+
+    /// Frozen account info for the recipient (if exists)
+    #[account(
+        seeds = [b"frozen", mint.key().as_ref(), to.as_ref()],
+        bump
+    )]
+    pub to_frozen_account: Option<Account<'info, FrozenAccount>>,
+
+    /// Holder's transfer volume counter (required with volume limits)
+    #[account(
+        seeds = [TRANSFER_COUNTER_SEED, mint.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub from_transfer_counter: Option<Account<'info, TransferCounter>>,
+
+    /// Escrow token account owned by the config PDA
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = mint,
+        associated_token::authority = config,
+        associated_token::token_program = token_program
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The transfer request to create
+    #[account(
+        init,
+        payer = holder,
+        space = TransferRequest::LEN,
+        seeds = [TRANSFER_REQUEST_SEED, mint.key().as_ref(), holder.key().as_ref(), &request_id.to_le_bytes()],
+        bump
+    )]
+    pub transfer_request: Account<'info, TransferRequest>,
+
+    /// Token program
+    pub token_program: Program<'info, Token2022>,
+
+    /// Associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn request_transfer<'info>(
+    ctx: Context<'_, '_, '_, 'info, RequestTransfer<'info>>,
+    request_id: u64,
+    to: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let mint = &ctx.accounts.mint;
+    let holder = &ctx.accounts.holder;
+    let from_token_account = &ctx.accounts.from_token_account;
+    let escrow_token_account = &ctx.accounts.escrow_token_account;
+
+    // Validate inputs
+    validate_amount(amount)?;
+    validate_pubkey(&to)?;
+    require!(to != holder.key(), ERC3643Error::SelfTransferNotAllowed);
+    require!(!config.paused, ERC3643Error::TokenPaused);
+
+    // Frozen tokens cannot be escrowed
+    let frozen_amount = match &ctx.accounts.from_frozen_account {
+        Some(frozen) => {
+            require!(!frozen.is_address_frozen(), ERC3643Error::AddressFrozen);
+            frozen.get_frozen_amount()
+        }
+        None => 0,
+    };
+    let spendable_balance = from_token_account.amount.saturating_sub(frozen_amount);
+    require!(amount <= spendable_balance, ERC3643Error::InsufficientBalance);
+    if let Some(frozen) = &ctx.accounts.to_frozen_account {
+        require!(!frozen.is_address_frozen(), ERC3643Error::AddressFrozen);
+    }
+
+    // Everything but the approval itself must already pass
+    let compliance_result = ctx.accounts.compliance_registry.check_transfer_compliance(
+        &TransferCheck {
+            from: holder.key(),
+            to,
+            amount,
+            from_balance: spendable_balance,
+            to_balance: ctx.accounts.to_token_account.amount,
+            supply: mint.supply,
+            from_country: ctx.accounts.from_identity_registry.country,
+            to_country: ctx.accounts.to_identity_registry.country,
+        },
+        ctx.accounts.from_transfer_counter.as_deref(),
+    )?;
+    require!(
+        compliance_result.allowed || compliance_result.requires_approval,
+        ERC3643Error::TransferNotCompliant
+    );
+
+    let mint_key = mint.key();
+    let modules = ctx.accounts.compliance_registry.custom_modules();
+    let module_accounts = hook_module_accounts(ctx.remaining_accounts, &mint_key);
+    let rejected_by = check_modules(
+        &modules,
+        module_accounts,
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: holder.key(),
+            to,
+            amount,
+        },
+    )?;
+    require!(rejected_by.is_none(), ERC3643Error::TransferNotCompliant);
+
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        escrow_token_account,
+        &mint.to_account_info(),
+        &config.to_account_info(),
+        signer_seeds,
+    )?;
+
+    transfer_as_permanent_delegate(
+        &ctx.accounts.token_program.to_account_info(),
+        &from_token_account.to_account_info(),
+        &mint.to_account_info(),
+        &escrow_token_account.to_account_info(),
+        &config.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+
+    notify_modules(
+        ModuleHook::Transfer,
+        &modules,
+        module_accounts,
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: holder.key(),
+            to: config.key(),
+            amount,
+        },
+    )?;
+
+    // Escrowed tokens no longer count towards the holder's position
+    let country = ctx.accounts.from_identity_registry.country;
+    let from_new_balance = from_token_account
+        .amount
+        .checked_sub(amount)
+        .ok_or(ERC3643Error::InsufficientBalance)?;
+    let compliance = &mut ctx.accounts.compliance_registry;
+    compliance.record_balance_change(country, from_token_account.amount, from_new_balance)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let expires_at = now
+        .checked_add(compliance.effective_transfer_request_ttl())
+        .ok_or(ERC3643Error::ArithmeticOverflow)?;
+
+    let request = &mut ctx.accounts.transfer_request;
+    request.mint = mint_key;
+    request.from = holder.key();
+    request.to = to;
+    request.amount = amount;
+    request.request_id = request_id;
+    request.created_at = now;
+    request.expires_at = expires_at;
+    request.reserved = [0; 32];
+
+    emit!(TransferRequested {
+        request: request.key(),
+        mint: mint_key,
+        from: holder.key(),
+        to,
+        amount,
+        expires_at,
+    });
+
+    msg!("Transfer request {} created: {} tokens from {} to {}", request_id, amount, holder.key(), to);
+    msg!("Expires at: {}", expires_at);
+
+    Ok(())
+}
+
+/// Approve a transfer request, releasing the escrow to the recipient
+///
+/// The transfer hook's extra accounts must be passed as remaining accounts;
+/// custom compliance modules check the transfer and are notified of the
+/// release through the module accounts among them.
+#[derive(Accounts)]
+pub struct ApproveTransferRequest<'info> {
+    /// Agent or compliance manager approving the transfer
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint account
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::TransferAgent)
+            || role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ ERC3643Error::TransferAgentRoleRequired
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// The request to approve, closed back to the holder
+    #[account(
+        mut,
+        close = holder,
+        seeds = [
+            TRANSFER_REQUEST_SEED,
+            mint.key().as_ref(),
+            transfer_request.from.as_ref(),
+            &transfer_request.request_id.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub transfer_request: Account<'info, TransferRequest>,
+
+    /// Holder that created the request, receives the request rent
+    /// CHECK: Address is checked against the transfer request
+    #[account(mut, address = transfer_request.from @ ERC3643Error::InvalidArgument)]
+    pub holder: UncheckedAccount<'info>,
+
+    /// Escrow token account owned by the config PDA
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config,
+        associated_token::token_program = token_program
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Recipient token account
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument,
+        constraint = to_token_account.owner == transfer_request.to @ ERC3643Error::InvalidArgument
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Identity registry for the holder (must still be verified)
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), transfer_request.from.as_ref()],
+        bump,
+        constraint = from_identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
+    )]
+    pub from_identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Identity registry for the recipient (must be verified)
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), transfer_request.to.as_ref()],
+        bump,
+        constraint = to_identity_registry.is_verified @ ERC3643Error::IdentityNotVerified
+    )]
+    pub to_identity_registry: Account<'info, IdentityRegistry>,
+
+    /// Frozen account info for the holder (if exists)
+    #[account(
+        seeds = [b"frozen", mint.key().as_ref(), transfer_request.from.as_ref()],
+        bump
+    )]
+    pub from_frozen_account: Option<Account<'info, FrozenAccount>>,
+
+    /// Frozen account info for the recipient (if exists)
+    #[account(
+        seeds = [b"frozen", mint.key().as_ref(), transfer_request.to.as_ref()],
+        bump
+    )]
+    pub to_frozen_account: Option<Account<'info, FrozenAccount>>,
+
+    /// Holder's transfer volume counter (required with volume limits)
+    #[account(
+        mut,
+        seeds = [TRANSFER_COUNTER_SEED, mint.key().as_ref(), transfer_request.from.as_ref()],
+        bump
+    )]
+    pub from_transfer_counter: Option<Account<'info, TransferCounter>>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
+    /// Token program
+    pub token_program: Program<'info, Token2022>,
+}
+
+pub fn approve_transfer_request<'info>(
+    ctx: Context<'_, '_, '_, 'info, ApproveTransferRequest<'info>>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let mint = &ctx.accounts.mint;
+    let request = &ctx.accounts.transfer_request;
+    let to_token_account = &ctx.accounts.to_token_account;
+    let authority = &ctx.accounts.authority;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(!config.paused, ERC3643Error::TokenPaused);
+    require!(!request.is_expired(now), ERC3643Error::TransferRequestExpired);
+    for frozen in [&ctx.accounts.from_frozen_account, &ctx.accounts.to_frozen_account].into_iter().flatten() {
+        require!(!frozen.is_address_frozen(), ERC3643Error::AddressFrozen);
+    }
+
+    // Rules may have changed since the request; only the approval is granted here
+    let compliance = &ctx.accounts.compliance_registry;
+    let compliance_result = compliance.check_transfer_compliance_at(
+        &TransferCheck {
+            from: request.from,
+            to: request.to,
+            amount: request.amount,
+            from_balance: request.amount,
+            to_balance: to_token_account.amount,
+            supply: mint.supply,
+            from_country: ctx.accounts.from_identity_registry.country,
+            to_country: ctx.accounts.to_identity_registry.country,
+        },
+        ctx.accounts.from_transfer_counter.as_deref(),
+        now,
+    )?;
+    require!(
+        compliance_result.allowed || compliance_result.requires_approval,
+        ERC3643Error::TransferNotCompliant
+    );
+
+    let mint_key = mint.key();
+    let modules = compliance.custom_modules();
+    let module_accounts = hook_module_accounts(ctx.remaining_accounts, &mint_key);
+    let rejected_by = check_modules(
+        &modules,
+        module_accounts,
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: request.from,
+            to: request.to,
+            amount: request.amount,
+        },
+    )?;
+    require!(rejected_by.is_none(), ERC3643Error::TransferNotCompliant);
+
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        to_token_account,
+        &mint.to_account_info(),
+        &config.to_account_info(),
+        signer_seeds,
+    )?;
+
+    transfer_as_permanent_delegate(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.escrow_token_account.to_account_info(),
+        &mint.to_account_info(),
+        &to_token_account.to_account_info(),
+        &config.to_account_info(),
+        ctx.remaining_accounts,
+        request.amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+
+    notify_modules(
+        ModuleHook::Transfer,
+        &modules,
+        module_accounts,
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: config.key(),
+            to: request.to,
+            amount: request.amount,
+        },
+    )?;
+
+    let compliance = &mut ctx.accounts.compliance_registry;
+    if let Some(counter) = ctx.accounts.from_transfer_counter.as_mut() {
+        counter.record_transfer(&compliance.transfer_limits, request.amount, now)?;
+    }
+
+    compliance.record_balance_change(
+        ctx.accounts.to_identity_registry.country,
+        to_token_account.amount,
+        to_token_account.amount.saturating_add(request.amount),
+    )?;
+
+    emit!(TransferRequestApproved {
+        request: request.key(),
+        from: request.from,
+        to: request.to,
+        amount: request.amount,
+        approved_by: authority.key(),
+    });
+
+    msg!("Transfer request {} approved by: {}", request.request_id, authority.key());
+    msg!("Released {} tokens to {}", request.amount, request.to);
+
+    Ok(())
+}
+
+/// Reject a transfer request, or refund one that expired
+///
+/// Rejection requires an agent or compliance manager; once the request has
+/// expired anyone may trigger the refund. The transfer hook's extra accounts
+/// must be passed as remaining accounts; custom compliance modules are
+/// notified of the refund through the module accounts among them.
+#[derive(Accounts)]
+pub struct RejectTransferRequest<'info> {
+    /// Agent, compliance manager, or anyone once the request expired
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token configuration
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint account
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Role registry
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// The request to reject, closed back to the holder
+    #[account(
+        mut,
+        close = holder,
+        seeds = [
+            TRANSFER_REQUEST_SEED,
+            mint.key().as_ref(),
+            transfer_request.from.as_ref(),
+            &transfer_request.request_id.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub transfer_request: Account<'info, TransferRequest>,
+
+    /// Holder that created the request, receives the request rent
+    /// CHECK: Address is checked against the transfer request
+    #[account(mut, address = transfer_request.from @ ERC3643Error::InvalidArgument)]
+    pub holder: UncheckedAccount<'info>,
+
+    /// Escrow token account owned by the config PDA
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config,
+        associated_token::token_program = token_program
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Holder token account receiving the refund
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key() @ ERC3643Error::InvalidArgument,
+        constraint = from_token_account.owner == transfer_request.from @ ERC3643Error::InvalidArgument
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Identity registry for the holder (if still registered), for investor counts
+    #[account(
+        seeds = [IDENTITY_SEED, mint.key().as_ref(), transfer_request.from.as_ref()],
+        bump
+    )]
+    pub from_identity_registry: Option<Account<'info, IdentityRegistry>>,
+
+    /// Compliance registry
+    #[account(
+        mut,
+        seeds = [COMPLIANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub compliance_registry: Account<'info, ComplianceRegistry>,
+
+    /// Token program
+    pub token_program: Program<'info, Token2022>,
+}
+
+pub fn reject_transfer_request<'info>(
+    ctx: Context<'_, '_, '_, 'info, RejectTransferRequest<'info>>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let mint = &ctx.accounts.mint;
+    let request = &ctx.accounts.transfer_request;
+    let from_token_account = &ctx.accounts.from_token_account;
+    let authority = &ctx.accounts.authority;
+
+    let expired = request.is_expired(Clock::get()?.unix_timestamp);
+    let roles = &ctx.accounts.role_registry;
+    let is_approver = roles.has_role(config, &authority.key(), Role::TransferAgent)
+        || roles.has_role(config, &authority.key(), Role::ComplianceManager);
+    require!(expired || is_approver, ERC3643Error::TransferRequestNotExpired);
+
+    let mint_key = mint.key();
+    let config_seeds = &[CONFIG_SEED, mint_key.as_ref(), &[ctx.bumps.config]];
+    let signer_seeds = &[&config_seeds[..]];
+
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        from_token_account,
+        &mint.to_account_info(),
+        &config.to_account_info(),
+        signer_seeds,
+    )?;
+
+    transfer_as_permanent_delegate(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.escrow_token_account.to_account_info(),
+        &mint.to_account_info(),
+        &from_token_account.to_account_info(),
+        &config.to_account_info(),
+        ctx.remaining_accounts,
+        request.amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+
+    notify_modules(
+        ModuleHook::Transfer,
+        &ctx.accounts.compliance_registry.custom_modules(),
+        hook_module_accounts(ctx.remaining_accounts, &mint_key),
+        &ModuleCall {
+            config: &config.to_account_info(),
+            mint: &mint.to_account_info(),
+            from: config.key(),
+            to: request.from,
+            amount: request.amount,
+        },
+    )?;
+
+    let country = ctx.accounts.from_identity_registry.as_ref().map_or(0, |identity| identity.country);
+    ctx.accounts.compliance_registry.record_balance_change(
+        country,
+        from_token_account.amount,
+        from_token_account.amount.saturating_add(request.amount),
+    )?;
+
+    if is_approver {
+        emit!(TransferRequestRejected {
+            request: request.key(),
+            from: request.from,
+            to: request.to,
+            amount: request.amount,
+            rejected_by: authority.key(),
+        });
+        msg!("Transfer request {} rejected by: {}", request.request_id, authority.key());
+    } else {
+        emit!(TransferRequestExpired {
+            request: request.key(),
+            from: request.from,
+            to: request.to,
+            amount: request.amount,
+        });
+        msg!("Transfer request {} expired", request.request_id);
+    }
+
+    msg!("Refunded {} tokens to {}", request.amount, request.from);
+
+    Ok(())
+}
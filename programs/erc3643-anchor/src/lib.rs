@@ -3,10 +3,15 @@ use anchor_lang::prelude::*;
 pub mod error;
 pub mod events;
 pub mod instructions;
+pub mod modules;
 pub mod state;
 pub mod utils;
 
 use instructions::*;
+use state::{
+    AdminAction, BatchResult, ComplianceAction, ComplianceModuleType, CountryRestriction, HoldingLimits,
+    IdentityVerificationResult, LockPeriod, Role, TradingWindow, TransferLimits,
+};
 
 declare_id!("DsZZehksyoo7hNKDZa4EVL8iq5FWTJnUypmw5DjSNvio");
 
@@ -19,10 +24,16 @@ pub mod erc3643_anchor {
         ctx: Context<InitializeToken>,
         name: String,
         symbol: String,
+        uri: String,
         decimals: u8,
         initial_supply: Option<u64>,
     ) -> Result<()> {
-        instructions::initialize_token::handler(ctx, name, symbol, decimals, initial_supply)
+        instructions::initialize_token::handler(ctx, name, symbol, uri, decimals, initial_supply)
+    }
+
+    /// Point the mint's transfer hook at a new program
+    pub fn set_transfer_hook(ctx: Context<SetTransferHook>) -> Result<()> {
+        instructions::initialize_token::set_transfer_hook(ctx)
     }
 
     /// Register a new identity in the registry
@@ -34,6 +45,25 @@ pub mod erc3643_anchor {
         instructions::identity::register_identity(ctx, user, country)
     }
 
+    /// Register identities for many users
+    pub fn batch_register_identity<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchRegisterIdentity<'info>>,
+        users: Vec<Pubkey>,
+        countries: Vec<u16>,
+    ) -> Result<BatchResult> {
+        instructions::batch::batch_register_identity(ctx, users, countries)
+    }
+
+    /// Bind this token's identity registry to a user's existing identity storage
+    pub fn bind_identity_storage(ctx: Context<BindIdentityStorage>, user: Pubkey) -> Result<()> {
+        instructions::identity::bind_identity_storage(ctx, user)
+    }
+
+    /// Unbind this token's identity registry from a user's identity storage
+    pub fn unbind_identity_storage(ctx: Context<UnbindIdentityStorage>) -> Result<()> {
+        instructions::identity::unbind_identity_storage(ctx)
+    }
+
     /// Add a claim to an identity
     pub fn add_claim(
         ctx: Context<AddClaim>,
@@ -42,8 +72,19 @@ pub mod erc3643_anchor {
         data: Vec<u8>,
         signature: Vec<u8>,
         uri: String,
+        expires_at: Option<i64>,
     ) -> Result<()> {
-        instructions::identity::add_claim(ctx, topic, scheme, data, signature, uri)
+        instructions::identity::add_claim(ctx, topic, scheme, data, signature, uri, expires_at)
+    }
+
+    /// Refresh a claim's data, signature and expiry
+    pub fn update_claim(
+        ctx: Context<UpdateClaim>,
+        data: Vec<u8>,
+        signature: Vec<u8>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        instructions::identity::update_claim(ctx, data, signature, expires_at)
     }
 
     /// Remove a claim from an identity
@@ -51,6 +92,16 @@ pub mod erc3643_anchor {
         instructions::identity::remove_claim(ctx, claim_id)
     }
 
+    /// Revoke a claim (issuer or token owner)
+    pub fn revoke_claim(ctx: Context<RevokeClaim>) -> Result<()> {
+        instructions::identity::revoke_claim(ctx)
+    }
+
+    /// Check whether a claim has been revoked
+    pub fn is_claim_revoked(ctx: Context<IsClaimRevoked>) -> Result<bool> {
+        instructions::identity::is_claim_revoked(ctx)
+    }
+
     /// Add a trusted issuer
     pub fn add_trusted_issuer(
         ctx: Context<AddTrustedIssuer>,
@@ -69,15 +120,42 @@ pub mod erc3643_anchor {
     }
 
     /// Mint tokens to a verified address
-    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+    pub fn mint_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, MintTokens<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::token::mint_tokens(ctx, amount)
     }
 
+    /// Thaw a verified holder's token account so it can receive transfers
+    pub fn thaw_token_account(ctx: Context<ThawTokenAccount>) -> Result<()> {
+        instructions::token::thaw_token_account(ctx)
+    }
+
     /// Burn tokens from an address
-    pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
+    pub fn burn_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, BurnTokens<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::token::burn_tokens(ctx, amount)
     }
 
+    /// Mint tokens to many verified addresses
+    pub fn batch_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchMint<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<BatchResult> {
+        instructions::batch::batch_mint(ctx, amounts)
+    }
+
+    /// Burn tokens from many addresses
+    pub fn batch_burn<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchBurn<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<BatchResult> {
+        instructions::batch::batch_burn(ctx, amounts)
+    }
+
     /// Set address frozen status
     pub fn set_address_frozen(
         ctx: Context<SetAddressFrozen>,
@@ -87,6 +165,15 @@ pub mod erc3643_anchor {
         instructions::admin::set_address_frozen(ctx, user, frozen)
     }
 
+    /// Set the frozen status of many addresses
+    pub fn batch_set_address_frozen<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchSetAddressFrozen<'info>>,
+        users: Vec<Pubkey>,
+        frozen: Vec<bool>,
+    ) -> Result<BatchResult> {
+        instructions::batch::batch_set_address_frozen(ctx, users, frozen)
+    }
+
     /// Freeze partial tokens
     pub fn freeze_partial_tokens(
         ctx: Context<FreezePartialTokens>,
@@ -111,8 +198,8 @@ pub mod erc3643_anchor {
     }
 
     /// Check if transfer is allowed (read-only)
-    pub fn check_transfer_allowed(
-        ctx: Context<CheckTransferAllowed>,
+    pub fn check_transfer_allowed<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckTransferAllowed<'info>>,
         from: Pubkey,
         to: Pubkey,
         amount: u64,
@@ -125,9 +212,14 @@ pub mod erc3643_anchor {
         instructions::compliance::check_verification(ctx, user)
     }
 
+    /// Verify an identity from all of its claims, raising its status if covered
+    pub fn verify_identity(ctx: Context<VerifyIdentity>) -> Result<IdentityVerificationResult> {
+        instructions::compliance::verify_identity(ctx)
+    }
+
     /// Forced transfer (agent only)
-    pub fn forced_transfer(
-        ctx: Context<ForcedTransfer>,
+    pub fn forced_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForcedTransfer<'info>>,
         from: Pubkey,
         to: Pubkey,
         amount: u64,
@@ -136,8 +228,8 @@ pub mod erc3643_anchor {
     }
 
     /// Recovery address (agent only)
-    pub fn recovery_address(
-        ctx: Context<RecoveryAddress>,
+    pub fn recovery_address<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecoveryAddress<'info>>,
         lost_wallet: Pubkey,
         new_wallet: Pubkey,
         investor_identity: Pubkey,
@@ -165,18 +257,251 @@ pub mod erc3643_anchor {
         instructions::identity::delete_identity(ctx)
     }
 
-    /// Add agent
-    pub fn add_agent(ctx: Context<AddAgent>, agent: Pubkey) -> Result<()> {
-        instructions::admin::add_agent(ctx, agent)
+    /// Add a lock period
+    pub fn add_lock_period(ctx: Context<AddLockPeriod>, lock_period: LockPeriod) -> Result<()> {
+        instructions::compliance_rules::add_lock_period(ctx, lock_period)
+    }
+
+    /// Update an existing lock period
+    pub fn update_lock_period(
+        ctx: Context<UpdateLockPeriod>,
+        index: u8,
+        lock_period: LockPeriod,
+    ) -> Result<()> {
+        instructions::compliance_rules::update_lock_period(ctx, index, lock_period)
     }
 
-    /// Remove agent
-    pub fn remove_agent(ctx: Context<RemoveAgent>, agent: Pubkey) -> Result<()> {
-        instructions::admin::remove_agent(ctx, agent)
+    /// Remove a lock period
+    pub fn remove_lock_period(ctx: Context<RemoveLockPeriod>, index: u8) -> Result<()> {
+        instructions::compliance_rules::remove_lock_period(ctx, index)
     }
 
-    /// Transfer ownership
+    /// Add a country restriction
+    pub fn add_country_restriction(
+        ctx: Context<AddCountryRestriction>,
+        restriction: CountryRestriction,
+    ) -> Result<()> {
+        instructions::compliance_rules::add_country_restriction(ctx, restriction)
+    }
+
+    /// Remove a country restriction
+    pub fn remove_country_restriction(
+        ctx: Context<RemoveCountryRestriction>,
+        country_code: u16,
+    ) -> Result<()> {
+        instructions::compliance_rules::remove_country_restriction(ctx, country_code)
+    }
+
+    /// Set transfer limits
+    pub fn set_transfer_limits(ctx: Context<SetTransferLimits>, limits: TransferLimits) -> Result<()> {
+        instructions::compliance_rules::set_transfer_limits(ctx, limits)
+    }
+
+    /// Set holding limits
+    pub fn set_holding_limits(ctx: Context<SetHoldingLimits>, limits: HoldingLimits) -> Result<()> {
+        instructions::compliance_rules::set_holding_limits(ctx, limits)
+    }
+
+    /// Add a trading window
+    pub fn add_trading_window(ctx: Context<AddTradingWindow>, window: TradingWindow) -> Result<()> {
+        instructions::compliance_rules::add_trading_window(ctx, window)
+    }
+
+    /// Remove a trading window
+    pub fn remove_trading_window(ctx: Context<RemoveTradingWindow>, index: u8) -> Result<()> {
+        instructions::compliance_rules::remove_trading_window(ctx, index)
+    }
+
+    /// Set the investor cap
+    pub fn set_max_investors(
+        ctx: Context<SetMaxInvestors>,
+        max_investors: Option<u32>,
+    ) -> Result<()> {
+        instructions::compliance_rules::set_max_investors(ctx, max_investors)
+    }
+
+    /// Set or remove the investor cap for a country
+    pub fn set_country_investor_limit(
+        ctx: Context<SetCountryInvestorLimit>,
+        country_code: u16,
+        max_investors: Option<u32>,
+        current_investors: u32,
+    ) -> Result<()> {
+        instructions::compliance_rules::set_country_investor_limit(
+            ctx,
+            country_code,
+            max_investors,
+            current_investors,
+        )
+    }
+
+    /// Add a compliance module, binding an external program for custom modules
+    pub fn add_compliance_module(
+        ctx: Context<AddComplianceModule>,
+        name: String,
+        module_type: ComplianceModuleType,
+        program_id: Pubkey,
+        account_count: u8,
+        parameters: Vec<u8>,
+    ) -> Result<()> {
+        instructions::compliance_rules::add_compliance_module(
+            ctx,
+            name,
+            module_type,
+            program_id,
+            account_count,
+            parameters,
+        )
+    }
+
+    /// Remove a compliance module
+    pub fn remove_compliance_module(ctx: Context<RemoveComplianceModule>, module_id: u32) -> Result<()> {
+        instructions::compliance_rules::remove_compliance_module(ctx, module_id)
+    }
+
+    /// Set the lifetime of transfer requests
+    pub fn set_transfer_request_ttl(ctx: Context<SetTransferRequestTtl>, ttl: i64) -> Result<()> {
+        instructions::compliance_rules::set_transfer_request_ttl(ctx, ttl)
+    }
+
+    /// Escrow tokens for a transfer that needs issuer approval
+    pub fn request_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RequestTransfer<'info>>,
+        request_id: u64,
+        to: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::transfer_request::request_transfer(ctx, request_id, to, amount)
+    }
+
+    /// Approve a transfer request (agent or compliance manager)
+    pub fn approve_transfer_request<'info>(
+        ctx: Context<'_, '_, '_, 'info, ApproveTransferRequest<'info>>,
+    ) -> Result<()> {
+        instructions::transfer_request::approve_transfer_request(ctx)
+    }
+
+    /// Reject a transfer request, or refund it once expired
+    pub fn reject_transfer_request<'info>(
+        ctx: Context<'_, '_, '_, 'info, RejectTransferRequest<'info>>,
+    ) -> Result<()> {
+        instructions::transfer_request::reject_transfer_request(ctx)
+    }
+
+    /// Add an agent with the given agent role
+    pub fn add_agent(ctx: Context<AddAgent>, agent: Pubkey, role: Role) -> Result<()> {
+        instructions::admin::add_agent(ctx, agent, role)
+    }
+
+    /// Remove an agent role
+    pub fn remove_agent(ctx: Context<RemoveAgent>, agent: Pubkey, role: Role) -> Result<()> {
+        instructions::admin::remove_agent(ctx, agent, role)
+    }
+
+    /// Set the compliance manager
+    pub fn set_compliance_manager(
+        ctx: Context<SetComplianceManager>,
+        compliance_manager: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::set_compliance_manager(ctx, compliance_manager)
+    }
+
+    /// Grant a role to a member
+    pub fn grant_role(ctx: Context<GrantRole>, member: Pubkey, role: Role) -> Result<()> {
+        instructions::admin::grant_role(ctx, member, role)
+    }
+
+    /// Revoke a role from a member
+    pub fn revoke_role(ctx: Context<RevokeRole>, member: Pubkey, role: Role) -> Result<()> {
+        instructions::admin::revoke_role(ctx, member, role)
+    }
+
+    /// Update the token name, symbol and onchain ID
+    pub fn set_token_information(
+        ctx: Context<SetTokenInformation>,
+        name: Option<String>,
+        symbol: Option<String>,
+        onchain_id: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::admin::set_token_information(ctx, name, symbol, onchain_id)
+    }
+
+    /// Set the supply cap enforced when minting
+    pub fn set_max_supply(ctx: Context<SetMaxSupply>, max_supply: Option<u64>) -> Result<()> {
+        instructions::admin::set_max_supply(ctx, max_supply)
+    }
+
+    /// Nominate a new owner, who must accept the transfer
     pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
         instructions::admin::transfer_ownership(ctx, new_owner)
     }
+
+    /// Accept a pending ownership transfer
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        instructions::admin::accept_ownership(ctx)
+    }
+
+    /// Cancel a pending ownership transfer
+    pub fn cancel_ownership_transfer(ctx: Context<CancelOwnershipTransfer>) -> Result<()> {
+        instructions::admin::cancel_ownership_transfer(ctx)
+    }
+
+    /// Hand sensitive owner actions over to an M-of-N multisig
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+        proposal_ttl: i64,
+    ) -> Result<()> {
+        instructions::multisig::create_multisig(ctx, approvers, threshold, proposal_ttl)
+    }
+
+    /// Propose an admin action to the multisig
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: AdminAction) -> Result<()> {
+        instructions::multisig::create_proposal(ctx, action)
+    }
+
+    /// Approve a multisig proposal
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        instructions::multisig::approve_proposal(ctx)
+    }
+
+    /// Execute a multisig proposal that reached the threshold
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        instructions::multisig::execute_proposal(ctx)
+    }
+
+    /// Cancel a multisig proposal, or close it once expired
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        instructions::multisig::cancel_proposal(ctx)
+    }
+
+    /// Set the delay applied to compliance changes (0 applies them immediately)
+    pub fn set_compliance_timelock(ctx: Context<SetComplianceTimelock>, delay: i64) -> Result<()> {
+        instructions::timelock::set_compliance_timelock(ctx, delay)
+    }
+
+    /// Queue a compliance change behind the timelock
+    pub fn queue_compliance_change(
+        ctx: Context<QueueComplianceChange>,
+        action: ComplianceAction,
+    ) -> Result<()> {
+        instructions::timelock::queue_compliance_change(ctx, action)
+    }
+
+    /// Execute a queued compliance change once its eta passed
+    ///
+    /// `current_investors` seeds the count of a country capped by the change
+    /// and is ignored otherwise.
+    pub fn execute_compliance_change(
+        ctx: Context<ExecuteComplianceChange>,
+        current_investors: u32,
+    ) -> Result<()> {
+        instructions::timelock::execute_compliance_change(ctx, current_investors)
+    }
+
+    /// Cancel a queued compliance change
+    pub fn cancel_compliance_change(ctx: Context<CancelComplianceChange>) -> Result<()> {
+        instructions::timelock::cancel_compliance_change(ctx)
+    }
 }
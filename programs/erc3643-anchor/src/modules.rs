@@ -0,0 +1,221 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke_signed},
+};
+use crate::{
+    error::ERC3643Error,
+    state::{ComplianceModule, MODULE_AUTHORITY_SEED},
+};
+
+/// Interface for external compliance modules
+///
+/// A `Custom` compliance module is a program bound to the mint's compliance
+/// registry. It is invoked with Anchor-style instructions (discriminator
+/// `sha256("global:<name>")[..8]` followed by `ModuleCallArgs`) and receives:
+///
+/// 0. the module authority PDA `["module_authority", mint]`, as signer
+/// 1. the token config PDA, read-only
+/// 2. the mint
+/// 3. the module's own state accounts, `account_count` of them
+///
+/// The module authority holds no token authority; modules check its
+/// signature to know a call comes from this program. `module_check` must set
+/// a borsh `bool` as return data. The action hooks let the module update its
+/// state once a transfer, mint or burn happened.
+///
+/// Callers pass the module authority followed by, for each active custom
+/// module in registry order, the module program and its state accounts as
+/// remaining accounts. Inside the transfer hook these are resolved through
+/// the extra account meta list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModuleHook {
+    Check,
+    Transfer,
+    Mint,
+    Burn,
+}
+
+impl ModuleHook {
+    pub fn instruction_name(&self) -> &'static str {
+        match self {
+            ModuleHook::Check => "module_check",
+            ModuleHook::Transfer => "module_transfer_action",
+            ModuleHook::Mint => "module_mint_action",
+            ModuleHook::Burn => "module_burn_action",
+        }
+    }
+
+    pub fn discriminator(&self) -> [u8; 8] {
+        let preimage = format!("global:{}", self.instruction_name());
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash(preimage.as_bytes()).to_bytes()[..8]);
+        discriminator
+    }
+}
+
+/// Arguments passed to every module instruction. `from` is the default
+/// pubkey for mints and `to` is the default pubkey for burns.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ModuleCallArgs {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub parameters: Vec<u8>,
+}
+
+/// Token accounts and operation passed to every module
+pub struct ModuleCall<'a, 'info> {
+    pub config: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
+
+/// Run `module_check` on every custom module, returning the name of the first
+/// module rejecting the operation
+pub fn check_modules<'info>(
+    modules: &[ComplianceModule],
+    accounts: &[AccountInfo<'info>],
+    call: &ModuleCall<'_, 'info>,
+) -> Result<Option<String>> {
+    if modules.is_empty() {
+        return Ok(None);
+    }
+
+    let (authority, bump, accounts) = module_authority(accounts, call.mint)?;
+    let mut cursor = 0;
+    for module in modules {
+        let (program, state) = module_accounts(module, accounts, &mut cursor)?;
+        invoke_module(ModuleHook::Check, module, program, state, (authority, bump), call)?;
+
+        let allowed = match get_return_data() {
+            Some((program_id, data)) if program_id == module.program_id => {
+                bool::try_from_slice(&data).map_err(|_| ERC3643Error::InvalidComplianceModuleResponse)?
+            }
+            _ => return Err(ERC3643Error::InvalidComplianceModuleResponse.into()),
+        };
+        if !allowed {
+            return Ok(Some(module.name.clone()));
+        }
+    }
+    Ok(None)
+}
+
+/// Notify every custom module of a completed transfer, mint or burn
+pub fn notify_modules<'info>(
+    hook: ModuleHook,
+    modules: &[ComplianceModule],
+    accounts: &[AccountInfo<'info>],
+    call: &ModuleCall<'_, 'info>,
+) -> Result<()> {
+    if modules.is_empty() {
+        return Ok(());
+    }
+
+    let (authority, bump, accounts) = module_authority(accounts, call.mint)?;
+    let mut cursor = 0;
+    for module in modules {
+        let (program, state) = module_accounts(module, accounts, &mut cursor)?;
+        invoke_module(hook, module, program, state, (authority, bump), call)?;
+    }
+    Ok(())
+}
+
+/// Module accounts within the transfer hook's extra accounts, starting at the
+/// module authority PDA, for instructions that move tokens as permanent
+/// delegate and so bypass the hook's module calls
+pub fn hook_module_accounts<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    mint: &Pubkey,
+) -> &'a [AccountInfo<'info>] {
+    let (authority, _) = Pubkey::find_program_address(&[MODULE_AUTHORITY_SEED, mint.as_ref()], &crate::ID);
+    accounts
+        .iter()
+        .position(|account| account.key() == authority)
+        .map_or(&[], |pos| &accounts[pos..])
+}
+
+/// Take the module authority PDA off the front of `accounts`
+fn module_authority<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    mint: &AccountInfo<'info>,
+) -> Result<(&'a AccountInfo<'info>, u8, &'a [AccountInfo<'info>])> {
+    let (authority, rest) = accounts
+        .split_first()
+        .ok_or(ERC3643Error::ComplianceModuleAccountsMismatch)?;
+    let (expected, bump) =
+        Pubkey::find_program_address(&[MODULE_AUTHORITY_SEED, mint.key.as_ref()], &crate::ID);
+    require!(authority.key() == expected, ERC3643Error::ComplianceModuleAccountsMismatch);
+    Ok((authority, bump, rest))
+}
+
+/// Take the next module's program and state accounts from `accounts`
+fn module_accounts<'a, 'info>(
+    module: &ComplianceModule,
+    accounts: &'a [AccountInfo<'info>],
+    cursor: &mut usize,
+) -> Result<(&'a AccountInfo<'info>, &'a [AccountInfo<'info>])> {
+    let end = *cursor + 1 + module.account_count as usize;
+    require!(end <= accounts.len(), ERC3643Error::ComplianceModuleAccountsMismatch);
+
+    let program = &accounts[*cursor];
+    require!(
+        program.key() == module.program_id && program.executable,
+        ERC3643Error::ComplianceModuleAccountsMismatch
+    );
+
+    let state = &accounts[*cursor + 1..end];
+    *cursor = end;
+    Ok((program, state))
+}
+
+fn invoke_module<'info>(
+    hook: ModuleHook,
+    module: &ComplianceModule,
+    program: &AccountInfo<'info>,
+    state: &[AccountInfo<'info>],
+    (authority, bump): (&AccountInfo<'info>, u8),
+    call: &ModuleCall<'_, 'info>,
+) -> Result<()> {
+    let mut data = hook.discriminator().to_vec();
+    ModuleCallArgs {
+        from: call.from,
+        to: call.to,
+        amount: call.amount,
+        parameters: module.parameters.clone(),
+    }
+    .serialize(&mut data)?;
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(authority.key(), true),
+        AccountMeta::new_readonly(call.config.key(), false),
+        AccountMeta::new_readonly(call.mint.key(), false),
+    ];
+    metas.extend(state.iter().map(|account| {
+        if account.is_writable {
+            AccountMeta::new(account.key(), false)
+        } else {
+            AccountMeta::new_readonly(account.key(), false)
+        }
+    }));
+
+    let mut account_infos = vec![authority.clone(), call.config.clone(), call.mint.clone()];
+    account_infos.extend(state.iter().cloned());
+    account_infos.push(program.clone());
+
+    let mint_key = call.mint.key();
+    let authority_seeds = &[MODULE_AUTHORITY_SEED, mint_key.as_ref(), &[bump]];
+    invoke_signed(
+        &Instruction {
+            program_id: module.program_id,
+            accounts: metas,
+            data,
+        },
+        &account_infos,
+        &[&authority_seeds[..]],
+    )
+    .map_err(Into::into)
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{keccak, secp256k1_recover::secp256k1_recover};
 use super::*;
 
 /// Individual claim account
@@ -37,9 +38,12 @@ pub struct Claim {
     
     /// Who revoked the claim (if revoked)
     pub revoked_by: Option<Pubkey>,
-    
+
+    /// Bumped on every update so older signed messages cannot be replayed
+    pub nonce: u64,
+
     /// Reserved space
-    pub reserved: [u8; 32],
+    pub reserved: [u8; 24],
 }
 
 impl Claim {
@@ -57,9 +61,12 @@ impl Claim {
         (1 + 8) + // expires_at (Option<i64>)
         (1 + 8) + // revoked_at (Option<i64>)
         (1 + 32) + // revoked_by (Option<Pubkey>)
-        32; // reserved
+        8 + // nonce
+        24; // reserved
 
     /// Create a new claim
+    // Mirrors the claim fields one to one
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         identity: Pubkey,
         issuer: Pubkey,
@@ -96,19 +103,24 @@ impl Claim {
             expires_at,
             revoked_at: None,
             revoked_by: None,
-            reserved: [0; 32],
+            nonce: 0,
+            reserved: [0; 24],
         })
     }
 
     /// Check if claim is currently valid
     pub fn is_currently_valid(&self) -> bool {
+        self.is_valid_at(Clock::get().unwrap().unix_timestamp)
+    }
+
+    /// Check if claim is valid at the given time
+    pub fn is_valid_at(&self, now: i64) -> bool {
         if !self.is_valid || self.is_revoked {
             return false;
         }
 
         // Check expiration
         if let Some(expiry) = self.expires_at {
-            let now = Clock::get().unwrap().unix_timestamp;
             if now > expiry {
                 return false;
             }
@@ -117,6 +129,19 @@ impl Claim {
         true
     }
 
+    /// Remove the claim, keeping the account as a tombstone so its signed
+    /// message cannot be replayed to bring it back
+    pub fn remove(&mut self) -> Result<()> {
+        require!(self.is_valid, crate::error::ERC3643Error::ClaimNotFound);
+        self.is_valid = false;
+        Ok(())
+    }
+
+    /// Whether the claim was removed and only its tombstone is left
+    pub fn is_removed(&self) -> bool {
+        self.issuer != Pubkey::default() && !self.is_valid
+    }
+
     /// Revoke the claim
     pub fn revoke(&mut self, revoked_by: Pubkey) -> Result<()> {
         require!(!self.is_revoked, crate::error::ERC3643Error::ClaimRevoked);
@@ -128,15 +153,29 @@ impl Claim {
         Ok(())
     }
 
-    /// Update claim data (only by issuer)
-    pub fn update_data(&mut self, data: Vec<u8>, signature: Vec<u8>) -> Result<()> {
+    /// Update claim data, signed by the issuer at the next nonce
+    pub fn update_data(
+        &mut self,
+        data: Vec<u8>,
+        signature: Vec<u8>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require!(self.is_valid, crate::error::ERC3643Error::ClaimNotFound);
         require!(!self.is_revoked, crate::error::ERC3643Error::ClaimRevoked);
         
         validate_data_size(&data, MAX_CLAIM_DATA_SIZE)?;
         validate_data_size(&signature, MAX_CLAIM_SIGNATURE_SIZE)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(expiry) = expires_at {
+            require!(expiry > now, crate::error::ERC3643Error::InvalidArgument);
+        }
         
         self.data = data;
         self.signature = signature;
+        self.expires_at = expires_at;
+        self.issued_at = now;
+        self.nonce = self.nonce.checked_add(1).ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
         
         Ok(())
     }
@@ -146,18 +185,74 @@ impl Claim {
         generate_claim_id(&self.issuer, self.topic)
     }
 
-    /// Validate claim signature (simplified - in production would verify cryptographic signature)
+    /// Message an issuer signs for a claim:
+    /// program id || identity || topic (LE) || nonce (LE) || expires_at (LE, 0 = never) || data
+    pub fn signed_message(
+        identity: &Pubkey,
+        topic: u64,
+        nonce: u64,
+        expires_at: Option<i64>,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(32 + 32 + 8 + 8 + 8 + data.len());
+        message.extend_from_slice(crate::ID.as_ref());
+        message.extend_from_slice(identity.as_ref());
+        message.extend_from_slice(&topic.to_le_bytes());
+        message.extend_from_slice(&nonce.to_le_bytes());
+        message.extend_from_slice(&expires_at.unwrap_or(0).to_le_bytes());
+        message.extend_from_slice(data);
+        message
+    }
+
+    /// Recover the issuer key of an ECDSA claim signed with an Ethereum key.
+    ///
+    /// The signature is a 65 byte (r, s, v) signature over the EIP-191 personal
+    /// message hash of `keccak256(message)`. The returned key holds the EVM
+    /// address left-padded with 12 zero bytes.
+    pub fn recover_ecdsa_issuer(message: &[u8], signature: &[u8]) -> Result<Pubkey> {
+        require!(signature.len() == 65, crate::error::ERC3643Error::InvalidClaimSignature);
+
+        let recovery_id = match signature[64] {
+            v @ 0..=1 => v,
+            v @ 27..=28 => v - 27,
+            _ => return Err(crate::error::ERC3643Error::InvalidClaimSignature.into()),
+        };
+
+        let message_hash = keccak::hash(message);
+        let digest = keccak::hashv(&[b"\x19Ethereum Signed Message:\n32", message_hash.as_ref()]);
+        let public_key = secp256k1_recover(digest.as_ref(), recovery_id, &signature[..64])
+            .map_err(|_| crate::error::ERC3643Error::InvalidClaimSignature)?;
+
+        let mut issuer = [0u8; 32];
+        issuer[12..].copy_from_slice(&keccak::hash(&public_key.to_bytes()).as_ref()[12..]);
+        Ok(Pubkey::new_from_array(issuer))
+    }
+
+    /// Validate claim signature.
+    ///
+    /// ECDSA signatures are recovered and checked against the issuer. Ed25519
+    /// signatures can only be verified through the Ed25519 program when the
+    /// claim is issued, so only their shape is checked here.
     pub fn validate_signature(&self) -> Result<bool> {
-        // In a real implementation, this would:
-        // 1. Reconstruct the signed message from identity + topic + data
-        // 2. Verify the signature against the issuer's public key
-        // 3. Check the signature scheme matches
-        
-        // For now, we'll do basic validation
         require!(!self.signature.is_empty(), crate::error::ERC3643Error::InvalidClaimSignature);
         require!(!self.data.is_empty(), crate::error::ERC3643Error::InvalidArgument);
-        
-        Ok(true)
+
+        match self.scheme {
+            ClaimScheme::ECDSA => {
+                let message = Self::signed_message(
+                    &self.identity,
+                    self.topic,
+                    self.nonce,
+                    self.expires_at,
+                    &self.data,
+                );
+                Ok(Self::recover_ecdsa_issuer(&message, &self.signature)
+                    .is_ok_and(|issuer| issuer == self.issuer))
+            }
+            ClaimScheme::Ed25519 => Ok(self.signature.len() == 64),
+            ClaimScheme::Contract => Ok(true),
+            ClaimScheme::RSA => Ok(false),
+        }
     }
 }
 
@@ -175,7 +270,7 @@ pub struct ClaimVerificationResult {
 impl ClaimVerificationResult {
     pub fn new(claim: &Claim, issuer_trusted: bool) -> Result<Self> {
         let now = Clock::get()?.unix_timestamp;
-        let is_expired = claim.expires_at.map_or(false, |exp| now > exp);
+        let is_expired = claim.expires_at.is_some_and(|exp| now > exp);
         let signature_valid = claim.validate_signature()?;
         
         Ok(Self {
@@ -203,6 +298,10 @@ pub struct IdentityVerificationResult {
 
 impl IdentityVerificationResult {
     pub fn new(identity: Pubkey) -> Self {
+        Self::new_at(identity, Clock::get().unwrap().unix_timestamp)
+    }
+
+    pub fn new_at(identity: Pubkey, verification_timestamp: i64) -> Self {
         Self {
             identity,
             is_verified: false,
@@ -210,7 +309,7 @@ impl IdentityVerificationResult {
             valid_claims: 0,
             required_topics_met: Vec::new(),
             missing_topics: Vec::new(),
-            verification_timestamp: Clock::get().unwrap().unix_timestamp,
+            verification_timestamp,
         }
     }
 
@@ -301,3 +400,151 @@ impl Claim {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::verify_required_claims_coverage_at;
+
+    const NOW: i64 = 1_700_000_000;
+    const REQUIRED: [u64; 2] = [claim_topics::KYC, claim_topics::AML];
+
+    fn claim(identity: Pubkey, issuer: Pubkey, topic: u64) -> Claim {
+        Claim {
+            identity,
+            issuer,
+            topic,
+            scheme: ClaimScheme::Contract,
+            data: vec![1],
+            signature: vec![1],
+            uri: String::new(),
+            is_valid: true,
+            is_revoked: false,
+            issued_at: 0,
+            expires_at: None,
+            revoked_at: None,
+            revoked_by: None,
+            nonce: 0,
+            reserved: [0; 24],
+        }
+    }
+
+    fn trusted(issuer: Pubkey, claim_topics: Vec<u64>) -> TrustedIssuersRegistry {
+        TrustedIssuersRegistry {
+            mint: Pubkey::new_unique(),
+            issuers: vec![TrustedIssuer {
+                issuer,
+                claim_topics,
+                added_at: 0,
+                is_active: true,
+            }],
+            created_at: 0,
+            updated_at: 0,
+            reserved: [0; 64],
+        }
+    }
+
+    #[test]
+    fn finalize_lists_the_missing_topics() {
+        let mut result = IdentityVerificationResult::new_at(Pubkey::new_unique(), NOW);
+        result.add_valid_claim(claim_topics::KYC);
+        result.add_valid_claim(claim_topics::KYC);
+        result.finalize(&REQUIRED);
+        assert!(!result.is_verified);
+        assert_eq!(result.required_topics_met, vec![claim_topics::KYC]);
+        assert_eq!(result.missing_topics, vec![claim_topics::AML]);
+
+        result.add_valid_claim(claim_topics::AML);
+        result.finalize(&REQUIRED);
+        assert!(result.is_verified);
+        assert!(result.missing_topics.is_empty());
+    }
+
+    #[test]
+    fn trusted_claims_cover_the_required_topics() {
+        let identity = Pubkey::new_unique();
+        let issuer = Pubkey::new_unique();
+        let issuers = trusted(issuer, REQUIRED.to_vec());
+        let claims = vec![
+            claim(identity, issuer, claim_topics::KYC),
+            claim(identity, issuer, claim_topics::AML),
+        ];
+
+        let result = verify_required_claims_coverage_at(&identity, &claims, &REQUIRED, &issuers, NOW);
+        assert!(result.is_verified);
+        assert_eq!(result.claims_checked, 2);
+        assert_eq!(result.valid_claims, 2);
+        assert_eq!(result.verification_timestamp, NOW);
+    }
+
+    #[test]
+    fn untrusted_and_foreign_claims_do_not_count() {
+        let identity = Pubkey::new_unique();
+        let issuer = Pubkey::new_unique();
+        let issuers = trusted(issuer, vec![claim_topics::KYC]);
+        let claims = vec![
+            claim(identity, issuer, claim_topics::AML),
+            claim(identity, Pubkey::new_unique(), claim_topics::AML),
+            claim(Pubkey::new_unique(), issuer, claim_topics::KYC),
+        ];
+
+        let result = verify_required_claims_coverage_at(&identity, &claims, &REQUIRED, &issuers, NOW);
+        assert!(!result.is_verified);
+        assert_eq!(result.valid_claims, 0);
+        assert_eq!(result.missing_topics, REQUIRED.to_vec());
+    }
+
+    #[test]
+    fn revoked_removed_and_expired_claims_lose_coverage() {
+        let identity = Pubkey::new_unique();
+        let issuer = Pubkey::new_unique();
+        let issuers = trusted(issuer, REQUIRED.to_vec());
+        let covered = |kyc: Claim| {
+            let claims = vec![kyc, claim(identity, issuer, claim_topics::AML)];
+            verify_required_claims_coverage_at(&identity, &claims, &REQUIRED, &issuers, NOW).is_verified
+        };
+        assert!(covered(claim(identity, issuer, claim_topics::KYC)));
+
+        let mut revoked = claim(identity, issuer, claim_topics::KYC);
+        revoked.is_revoked = true;
+        assert!(!covered(revoked));
+
+        let mut removed = claim(identity, issuer, claim_topics::KYC);
+        removed.remove().unwrap();
+        assert!(!covered(removed));
+
+        let mut expired = claim(identity, issuer, claim_topics::KYC);
+        expired.expires_at = Some(NOW);
+        assert!(covered(expired.clone()));
+        expired.expires_at = Some(NOW - 1);
+        assert!(!covered(expired));
+    }
+
+    #[test]
+    fn removed_claims_stay_as_tombstones() {
+        let mut claim = claim(Pubkey::new_unique(), Pubkey::new_unique(), claim_topics::KYC);
+        assert!(!claim.is_removed());
+
+        claim.remove().unwrap();
+        assert!(claim.is_removed());
+        assert!(claim.remove().is_err());
+        assert!(claim.update_data(vec![2], vec![2], None).is_err());
+        assert_eq!(claim.nonce, 0);
+
+        let mut empty = claim.clone();
+        empty.issuer = Pubkey::default();
+        assert!(!empty.is_removed());
+    }
+
+    #[test]
+    fn signed_message_binds_the_program_and_nonce() {
+        let identity = Pubkey::new_unique();
+        let message = Claim::signed_message(&identity, claim_topics::KYC, 0, None, b"data");
+        assert_eq!(&message[..32], crate::ID.as_ref());
+        assert_eq!(&message[32..64], identity.as_ref());
+        assert!(message.ends_with(b"data"));
+
+        let next = Claim::signed_message(&identity, claim_topics::KYC, 1, None, b"data");
+        assert_ne!(message, next);
+    }
+}
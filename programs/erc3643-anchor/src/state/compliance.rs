@@ -23,6 +23,9 @@ pub struct ComplianceRegistry {
     /// Investor limits
     pub max_investors: Option<u32>,
     pub current_investors: u32,
+
+    /// Per-country investor caps
+    pub country_investor_limits: Vec<CountryInvestorLimit>,
     
     /// Compliance flags
     pub require_whitelist: bool,
@@ -33,63 +36,124 @@ pub struct ComplianceRegistry {
     /// Timestamps
     pub created_at: i64,
     pub updated_at: i64,
+
+    /// Lifetime of pending transfer requests in seconds (0 = default)
+    pub transfer_request_ttl: i64,
+
+    /// Delay in seconds before queued compliance changes apply (0 = changes apply immediately)
+    pub timelock_delay: i64,
+
+    /// Id assigned to the next queued compliance change
+    pub queued_change_count: u64,
     
     /// Reserved space
-    pub reserved: [u8; 64],
+    pub reserved: [u8; 40],
 }
 
 impl ComplianceRegistry {
     pub const LEN: usize = 8 + // discriminator
         32 + // mint
-        (4 + 10 * ComplianceModule::LEN) + // modules (max 10)
-        (4 + 50 * CountryRestriction::LEN) + // country_restrictions (max 50)
+        (4 + MAX_COMPLIANCE_MODULES * ComplianceModule::LEN) + // modules (max 10)
+        (4 + MAX_COUNTRY_RESTRICTIONS * CountryRestriction::LEN) + // country_restrictions (max 50)
         TransferLimits::LEN + // transfer_limits
         HoldingLimits::LEN + // holding_limits
-        (4 + 10 * LockPeriod::LEN) + // lock_periods (max 10)
-        (4 + 10 * TradingWindow::LEN) + // trading_windows (max 10)
+        (4 + MAX_LOCK_PERIODS * LockPeriod::LEN) + // lock_periods (max 10)
+        (4 + MAX_TRADING_WINDOWS * TradingWindow::LEN) + // trading_windows (max 10)
         (1 + 4) + // max_investors (Option<u32>)
         4 + // current_investors
+        (4 + MAX_COUNTRY_INVESTOR_LIMITS * CountryInvestorLimit::LEN) + // country_investor_limits (max 20)
         1 + // require_whitelist
         1 + // enable_blacklist
         1 + // require_kyc
         1 + // require_aml
         8 + // created_at
         8 + // updated_at
-        64; // reserved
+        8 + // transfer_request_ttl
+        8 + // timelock_delay
+        8 + // queued_change_count
+        40; // reserved
+
+    /// Default lifetime of pending transfer requests
+    pub const DEFAULT_TRANSFER_REQUEST_TTL: i64 = 7 * 86_400;
 
     /// Check if transfer is compliant
     pub fn check_transfer_compliance(
         &self,
-        from: &Pubkey,
-        to: &Pubkey,
-        amount: u64,
-        from_balance: u64,
-        to_balance: u64,
-        from_country: u16,
-        to_country: u16,
+        transfer: &TransferCheck,
+        from_counter: Option<&TransferCounter>,
     ) -> Result<ComplianceResult> {
+        let now = Clock::get()?.unix_timestamp;
+        self.check_transfer_compliance_at(transfer, from_counter, now)
+    }
+
+    /// Check if transfer is compliant at time `now`
+    pub fn check_transfer_compliance_at(
+        &self,
+        transfer: &TransferCheck,
+        from_counter: Option<&TransferCounter>,
+        now: i64,
+    ) -> Result<ComplianceResult> {
+        let TransferCheck {
+            from,
+            to,
+            amount,
+            from_balance,
+            to_balance,
+            supply,
+            from_country,
+            to_country,
+        } = *transfer;
+        let (from, to) = (&from, &to);
+
         let mut result = ComplianceResult {
             allowed: true,
             reason: String::new(),
             module: "base".to_string(),
+            requires_approval: false,
         };
 
-        // Check country restrictions
-        if let Err(e) = self.check_country_restrictions(from_country, to_country) {
+        // Check sender balance (minting has no sender)
+        if *from != Pubkey::default() && amount > from_balance {
             result.allowed = false;
-            result.reason = format!("Country restriction: {}", e);
+            result.reason = "Insufficient balance".to_string();
             return Ok(result);
         }
 
+        // Check country restrictions (minting has no sender country).
+        // Approval is only reported once every other rule passed
+        let mut approval = None;
+        let sender_country = (*from != Pubkey::default()).then_some(from_country);
+        match self.check_country_restrictions(sender_country, to_country) {
+            Ok(false) => {}
+            Ok(true) => {
+                approval = Some(("base", "Country restriction: transfer requires issuer approval"));
+            }
+            Err(e) => {
+                result.allowed = false;
+                result.reason = format!("Country restriction: {}", e);
+                return Ok(result);
+            }
+        }
+
+        // Conditional transfers must go through the approval queue
+        if *from != Pubkey::default() && self.has_active_module(ComplianceModuleType::ConditionalTransfer) {
+            approval = Some(("conditional_transfer", "Conditional transfer: transfer requires issuer approval"));
+        }
+
         // Check transfer limits
-        if let Err(e) = self.check_transfer_limits(amount) {
+        if let Err(e) = self.check_transfer_limits(from, amount, from_counter, now) {
             result.allowed = false;
             result.reason = format!("Transfer limit: {}", e);
             return Ok(result);
         }
 
         // Check holding limits
-        if let Err(e) = self.check_holding_limits(to_balance + amount) {
+        // A percentage of supply is meaningless for the very first issuance
+        let first_issuance = *from == Pubkey::default() && supply == amount;
+        let new_balance = to_balance
+            .checked_add(amount)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+        if let Err(e) = self.check_holding_limits(new_balance, supply, first_issuance) {
             result.allowed = false;
             result.reason = format!("Holding limit: {}", e);
             return Ok(result);
@@ -102,54 +166,126 @@ impl ComplianceRegistry {
             return Ok(result);
         }
 
+        if to_balance == 0 && self.is_country_investor_limit_reached(to_country) {
+            result.allowed = false;
+            result.reason = format!("Maximum investors reached for country {}", to_country);
+            return Ok(result);
+        }
+
         // Check lock periods
-        if let Err(e) = self.check_lock_periods(from) {
+        if let Err(e) = self.check_lock_periods(from, to, now) {
             result.allowed = false;
             result.reason = format!("Lock period: {}", e);
             return Ok(result);
         }
 
         // Check trading windows
-        if let Err(e) = self.check_trading_windows() {
+        if let Err(e) = self.check_trading_windows(now) {
             result.allowed = false;
             result.reason = format!("Trading window: {}", e);
             return Ok(result);
         }
 
+        if let Some((module, reason)) = approval {
+            result.allowed = false;
+            result.requires_approval = true;
+            result.module = module.to_string();
+            result.reason = reason.to_string();
+        }
+
         Ok(result)
     }
 
-    /// Check country restrictions
-    fn check_country_restrictions(&self, from_country: u16, to_country: u16) -> Result<()> {
-        for restriction in &self.country_restrictions {
-            if restriction.applies_to_country(from_country) || restriction.applies_to_country(to_country) {
-                if restriction.is_blocked() {
+    /// Check country restrictions, returning whether the transfer needs
+    /// issuer approval.
+    ///
+    /// Once any `Allowed` entry is active the registry is in allow-list mode
+    /// and countries without an entry are rejected.
+    fn check_country_restrictions(&self, from_country: Option<u16>, to_country: u16) -> Result<bool> {
+        let allow_list = self
+            .country_restrictions
+            .iter()
+            .any(|r| r.is_active && r.restriction_type == RestrictionType::Allowed);
+        let mut requires_approval = false;
+
+        for country in [from_country, Some(to_country)].into_iter().flatten() {
+            let restriction = self
+                .country_restrictions
+                .iter()
+                .find(|r| r.applies_to_country(country));
+
+            match restriction.map(|r| &r.restriction_type) {
+                Some(RestrictionType::Blocked) => {
                     return Err(crate::error::ERC3643Error::CountryNotAllowed.into());
                 }
+                Some(RestrictionType::RequiresApproval) => requires_approval = true,
+                Some(RestrictionType::Allowed) => {}
+                None => require!(!allow_list, crate::error::ERC3643Error::CountryNotAllowed),
             }
         }
-        Ok(())
+
+        Ok(requires_approval)
     }
 
-    /// Check transfer limits
-    fn check_transfer_limits(&self, amount: u64) -> Result<()> {
-        if let Some(max_transfer) = self.transfer_limits.max_transfer_amount {
+    /// Check transfer limits, including the sender's daily and monthly volume
+    fn check_transfer_limits(
+        &self,
+        from: &Pubkey,
+        amount: u64,
+        from_counter: Option<&TransferCounter>,
+        now: i64,
+    ) -> Result<()> {
+        let limits = &self.transfer_limits;
+
+        if let Some(max_transfer) = limits.max_transfer_amount {
             require!(amount <= max_transfer, crate::error::ERC3643Error::TransferLimitExceeded);
         }
 
-        if let Some(min_transfer) = self.transfer_limits.min_transfer_amount {
+        if let Some(min_transfer) = limits.min_transfer_amount {
             require!(amount >= min_transfer, crate::error::ERC3643Error::InvalidAmount);
         }
 
+        // Minting has no sender volume to track
+        if *from == Pubkey::default() || !limits.has_volume_limits() {
+            return Ok(());
+        }
+
+        let counter = from_counter.ok_or(crate::error::ERC3643Error::TransferCounterNotFound)?;
+        let (daily_volume, monthly_volume) = counter.current_volumes(limits, now);
+
+        if let Some(daily_limit) = limits.daily_limit {
+            require!(
+                daily_volume.saturating_add(amount) <= daily_limit,
+                crate::error::ERC3643Error::DailyLimitExceeded
+            );
+        }
+
+        if let Some(monthly_limit) = limits.monthly_limit {
+            require!(
+                monthly_volume.saturating_add(amount) <= monthly_limit,
+                crate::error::ERC3643Error::MonthlyLimitExceeded
+            );
+        }
+
         Ok(())
     }
 
-    /// Check holding limits
-    fn check_holding_limits(&self, new_balance: u64) -> Result<()> {
+    /// Check holding limits, including the share of `supply` held
+    fn check_holding_limits(&self, new_balance: u64, supply: u64, first_issuance: bool) -> Result<()> {
         if let Some(max_holding) = self.holding_limits.max_holding_amount {
             require!(new_balance <= max_holding, crate::error::ERC3643Error::MaximumHoldingExceeded);
         }
 
+        if let Some(percentage) = self.holding_limits.max_holding_percentage {
+            if !first_issuance {
+                let max_holding = supply as u128 * percentage as u128 / 10_000;
+                require!(
+                    new_balance as u128 <= max_holding,
+                    crate::error::ERC3643Error::MaximumHoldingExceeded
+                );
+            }
+        }
+
         if let Some(min_holding) = self.holding_limits.min_holding_amount {
             require!(new_balance >= min_holding, crate::error::ERC3643Error::MinimumHoldingNotMet);
         }
@@ -157,12 +293,22 @@ impl ComplianceRegistry {
         Ok(())
     }
 
-    /// Check lock periods
-    fn check_lock_periods(&self, user: &Pubkey) -> Result<()> {
-        let now = Clock::get()?.unix_timestamp;
-        
+    /// Check lock periods for both sides of the transfer
+    fn check_lock_periods(&self, from: &Pubkey, to: &Pubkey, now: i64) -> Result<()> {
         for lock_period in &self.lock_periods {
-            if lock_period.applies_to_user(user) && lock_period.is_active(now) {
+            if !lock_period.is_active(now) {
+                continue;
+            }
+
+            // Minting has no sender to lock
+            if *from != Pubkey::default()
+                && lock_period.applies_to_user(from)
+                && lock_period.blocks_sending()
+            {
+                return Err(crate::error::ERC3643Error::LockPeriodActive.into());
+            }
+
+            if lock_period.applies_to_user(to) && lock_period.blocks_receiving() {
                 return Err(crate::error::ERC3643Error::LockPeriodActive.into());
             }
         }
@@ -170,10 +316,43 @@ impl ComplianceRegistry {
         Ok(())
     }
 
+    /// Add a lock period
+    pub fn add_lock_period(&mut self, lock_period: LockPeriod) -> Result<()> {
+        lock_period.validate()?;
+        require!(
+            self.lock_periods.len() < MAX_LOCK_PERIODS,
+            crate::error::ERC3643Error::ComplianceRuleLimitReached
+        );
+        self.lock_periods.push(lock_period);
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Replace the lock period at `index`
+    pub fn update_lock_period(&mut self, index: u8, lock_period: LockPeriod) -> Result<()> {
+        lock_period.validate()?;
+        let existing = self
+            .lock_periods
+            .get_mut(index as usize)
+            .ok_or(crate::error::ERC3643Error::ComplianceRuleNotFound)?;
+        *existing = lock_period;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Remove the lock period at `index`
+    pub fn remove_lock_period(&mut self, index: u8) -> Result<LockPeriod> {
+        require!(
+            (index as usize) < self.lock_periods.len(),
+            crate::error::ERC3643Error::ComplianceRuleNotFound
+        );
+        let removed = self.lock_periods.remove(index as usize);
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(removed)
+    }
+
     /// Check trading windows
-    fn check_trading_windows(&self) -> Result<()> {
-        let now = Clock::get()?.unix_timestamp;
-        
+    fn check_trading_windows(&self, now: i64) -> Result<()> {
         // If no trading windows defined, allow all times
         if self.trading_windows.is_empty() {
             return Ok(());
@@ -189,14 +368,232 @@ impl ComplianceRegistry {
         Err(crate::error::ERC3643Error::TransferNotCompliant.into())
     }
 
-    /// Add compliance module
-    pub fn add_module(&mut self, module: ComplianceModule) -> Result<()> {
-        require!(self.modules.len() < 10, crate::error::ERC3643Error::TooManyTrustedIssuers);
-        self.modules.push(module);
+    /// Whether a new investor from `country` would exceed its cap
+    pub fn is_country_investor_limit_reached(&self, country: u16) -> bool {
+        self.country_investor_limits
+            .iter()
+            .any(|l| l.country_code == country && l.current_investors >= l.max_investors)
+    }
+
+    /// Maintain investor counts when a holder's balance goes from zero to
+    /// nonzero or back
+    pub fn record_balance_change(
+        &mut self,
+        country: u16,
+        previous_balance: u64,
+        new_balance: u64,
+    ) -> Result<()> {
+        let joined = previous_balance == 0 && new_balance > 0;
+        let left = previous_balance > 0 && new_balance == 0;
+        if !joined && !left {
+            return Ok(());
+        }
+
+        let country_limit = self
+            .country_investor_limits
+            .iter_mut()
+            .find(|l| l.country_code == country);
+
+        if joined {
+            self.current_investors = self.current_investors
+                .checked_add(1)
+                .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+            if let Some(limit) = country_limit {
+                limit.current_investors = limit.current_investors.saturating_add(1);
+            }
+        } else {
+            self.current_investors = self.current_investors.saturating_sub(1);
+            if let Some(limit) = country_limit {
+                limit.current_investors = limit.current_investors.saturating_sub(1);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set or remove the investor cap for a country.
+    ///
+    /// `current_investors` seeds the count with holders the country already
+    /// has when the cap is first set, since counts are only kept for
+    /// countries with a cap. Changing an existing cap keeps the live count.
+    pub fn set_country_investor_limit(
+        &mut self,
+        country_code: u16,
+        max_investors: Option<u32>,
+        current_investors: u32,
+    ) -> Result<()> {
+        validate_country_code(country_code)?;
+        let pos = self
+            .country_investor_limits
+            .iter()
+            .position(|l| l.country_code == country_code);
+
+        match (max_investors, pos) {
+            (Some(max), Some(pos)) => {
+                self.country_investor_limits[pos].max_investors = max;
+            }
+            (Some(max), None) => {
+                require!(
+                    self.country_investor_limits.len() < MAX_COUNTRY_INVESTOR_LIMITS,
+                    crate::error::ERC3643Error::ComplianceRuleLimitReached
+                );
+                self.country_investor_limits.push(CountryInvestorLimit {
+                    country_code,
+                    max_investors: max,
+                    current_investors,
+                });
+            }
+            (None, Some(pos)) => {
+                self.country_investor_limits.remove(pos);
+            }
+            (None, None) => return Err(crate::error::ERC3643Error::ComplianceRuleNotFound.into()),
+        }
+
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Whether an active module of the given type is configured
+    pub fn has_active_module(&self, module_type: ComplianceModuleType) -> bool {
+        self.modules
+            .iter()
+            .any(|m| m.is_active && m.module_type == module_type)
+    }
+
+    /// Lifetime of new transfer requests
+    pub fn effective_transfer_request_ttl(&self) -> i64 {
+        if self.transfer_request_ttl > 0 {
+            self.transfer_request_ttl
+        } else {
+            Self::DEFAULT_TRANSFER_REQUEST_TTL
+        }
+    }
+
+    /// Set the lifetime of new transfer requests (0 restores the default)
+    pub fn set_transfer_request_ttl(&mut self, ttl: i64) -> Result<()> {
+        require!(ttl >= 0, crate::error::ERC3643Error::InvalidComplianceConfiguration);
+        self.transfer_request_ttl = ttl;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Check if compliance changes must be queued behind the timelock
+    pub fn has_timelock(&self) -> bool {
+        self.timelock_delay > 0
+    }
+
+    /// Set the compliance timelock delay (0 disables the timelock)
+    pub fn set_timelock_delay(&mut self, delay: i64) -> Result<()> {
+        require!(delay >= 0, crate::error::ERC3643Error::InvalidComplianceConfiguration);
+        self.timelock_delay = delay;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Add a country restriction
+    pub fn add_country_restriction(&mut self, restriction: CountryRestriction) -> Result<()> {
+        restriction.validate()?;
+        require!(
+            !self
+                .country_restrictions
+                .iter()
+                .any(|r| r.country_code == restriction.country_code),
+            crate::error::ERC3643Error::InvalidComplianceConfiguration
+        );
+        require!(
+            self.country_restrictions.len() < MAX_COUNTRY_RESTRICTIONS,
+            crate::error::ERC3643Error::ComplianceRuleLimitReached
+        );
+        self.country_restrictions.push(restriction);
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Remove the restriction for a country
+    pub fn remove_country_restriction(&mut self, country_code: u16) -> Result<()> {
+        let pos = self
+            .country_restrictions
+            .iter()
+            .position(|r| r.country_code == country_code)
+            .ok_or(crate::error::ERC3643Error::ComplianceRuleNotFound)?;
+        self.country_restrictions.remove(pos);
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Replace the transfer limits
+    pub fn set_transfer_limits(&mut self, limits: TransferLimits) -> Result<()> {
+        limits.validate()?;
+        self.transfer_limits = limits;
         self.updated_at = Clock::get()?.unix_timestamp;
         Ok(())
     }
 
+    /// Replace the holding limits
+    pub fn set_holding_limits(&mut self, limits: HoldingLimits) -> Result<()> {
+        limits.validate()?;
+        self.holding_limits = limits;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Add a trading window
+    pub fn add_trading_window(&mut self, window: TradingWindow) -> Result<()> {
+        window.validate()?;
+        require!(
+            self.trading_windows.len() < MAX_TRADING_WINDOWS,
+            crate::error::ERC3643Error::ComplianceRuleLimitReached
+        );
+        self.trading_windows.push(window);
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Remove the trading window at `index`
+    pub fn remove_trading_window(&mut self, index: u8) -> Result<TradingWindow> {
+        require!(
+            (index as usize) < self.trading_windows.len(),
+            crate::error::ERC3643Error::ComplianceRuleNotFound
+        );
+        let removed = self.trading_windows.remove(index as usize);
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(removed)
+    }
+
+    /// Set the investor cap (None removes it)
+    pub fn set_max_investors(&mut self, max_investors: Option<u32>) -> Result<()> {
+        if let Some(max) = max_investors {
+            require!(max > 0, crate::error::ERC3643Error::InvalidComplianceConfiguration);
+        }
+        self.max_investors = max_investors;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Add compliance module, assigning it the next free id
+    pub fn add_module(&mut self, mut module: ComplianceModule) -> Result<u32> {
+        require!(
+            self.modules.len() < MAX_COMPLIANCE_MODULES,
+            crate::error::ERC3643Error::ComplianceRuleLimitReached
+        );
+        module.validate()?;
+        module.id = self.modules.iter().map(|m| m.id).max().map_or(1, |id| id + 1);
+        let id = module.id;
+        self.modules.push(module);
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(id)
+    }
+
+    /// Active modules implemented by external programs, in the order their
+    /// accounts are expected
+    pub fn custom_modules(&self) -> Vec<ComplianceModule> {
+        self.modules
+            .iter()
+            .filter(|m| m.is_active && m.module_type == ComplianceModuleType::Custom)
+            .cloned()
+            .collect()
+    }
+
     /// Remove compliance module
     pub fn remove_module(&mut self, module_id: u32) -> Result<()> {
         if let Some(pos) = self.modules.iter().position(|m| m.id == module_id) {
@@ -209,6 +606,22 @@ impl ComplianceRegistry {
     }
 }
 
+/// A transfer, mint or burn checked against the compliance rules
+///
+/// `from` is the default pubkey for mints. Balances are the amounts held
+/// before the operation and `supply` is the mint supply once it completes.
+#[derive(Clone, Copy, Debug)]
+pub struct TransferCheck {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub from_balance: u64,
+    pub to_balance: u64,
+    pub supply: u64,
+    pub from_country: u16,
+    pub to_country: u16,
+}
+
 /// Compliance module configuration
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ComplianceModule {
@@ -217,6 +630,10 @@ pub struct ComplianceModule {
     pub module_type: ComplianceModuleType,
     pub is_active: bool,
     pub parameters: Vec<u8>, // Serialized module-specific parameters
+    /// Program implementing a `Custom` module
+    pub program_id: Pubkey,
+    /// Number of module state accounts passed after the module program
+    pub account_count: u8,
 }
 
 impl ComplianceModule {
@@ -224,7 +641,20 @@ impl ComplianceModule {
         (4 + 32) + // name (max 32 chars)
         1 + // module_type
         1 + // is_active
-        (4 + 256); // parameters (max 256 bytes)
+        (4 + 256) + // parameters (max 256 bytes)
+        32 + // program_id
+        1; // account_count
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            !self.name.is_empty() && self.name.len() <= 32 && self.parameters.len() <= 256,
+            crate::error::ERC3643Error::InvalidComplianceModule
+        );
+        if self.module_type == ComplianceModuleType::Custom {
+            validate_pubkey(&self.program_id)?;
+        }
+        Ok(())
+    }
 }
 
 /// Types of compliance modules
@@ -238,6 +668,8 @@ pub enum ComplianceModuleType {
     Whitelist,
     Blacklist,
     Custom,
+    /// Every transfer needs issuer approval through a transfer request
+    ConditionalTransfer,
 }
 
 /// Country restriction configuration
@@ -260,6 +692,25 @@ impl CountryRestriction {
     pub fn is_blocked(&self) -> bool {
         matches!(self.restriction_type, RestrictionType::Blocked)
     }
+
+    /// Validate the country code
+    pub fn validate(&self) -> Result<()> {
+        validate_country_code(self.country_code)
+    }
+}
+
+/// Investor cap for a single country
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CountryInvestorLimit {
+    pub country_code: u16,
+    pub max_investors: u32,
+    pub current_investors: u32,
+}
+
+impl CountryInvestorLimit {
+    pub const LEN: usize = 2 + // country_code
+        4 + // max_investors
+        4; // current_investors
 }
 
 /// Types of country restrictions
@@ -277,7 +728,7 @@ pub struct TransferLimits {
     pub max_transfer_amount: Option<u64>,
     pub daily_limit: Option<u64>,
     pub monthly_limit: Option<u64>,
-    pub reset_period: u32, // seconds
+    pub reset_period: u32, // daily window in seconds (0 = one day)
 }
 
 impl TransferLimits {
@@ -286,6 +737,127 @@ impl TransferLimits {
         (1 + 8) + // daily_limit
         (1 + 8) + // monthly_limit
         4; // reset_period
+
+    /// Validate the limits are consistent with each other
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(min), Some(max)) = (self.min_transfer_amount, self.max_transfer_amount) {
+            require!(min <= max, crate::error::ERC3643Error::InvalidComplianceConfiguration);
+        }
+        if let (Some(daily), Some(monthly)) = (self.daily_limit, self.monthly_limit) {
+            require!(daily <= monthly, crate::error::ERC3643Error::InvalidComplianceConfiguration);
+        }
+        Ok(())
+    }
+
+    /// Default length of the daily window
+    pub const DAY: i64 = 86_400;
+
+    /// Length of the monthly window
+    pub const MONTH: i64 = 30 * Self::DAY;
+
+    /// Whether any volume limit needs per-holder counters
+    pub fn has_volume_limits(&self) -> bool {
+        self.daily_limit.is_some() || self.monthly_limit.is_some()
+    }
+
+    /// Length of the daily window; `reset_period` overrides the default day
+    pub fn daily_window(&self) -> i64 {
+        if self.reset_period > 0 {
+            self.reset_period as i64
+        } else {
+            Self::DAY
+        }
+    }
+}
+
+/// Outgoing transfer volume of a holder for the daily and monthly limits
+/// PDA: ["transfer_counter", mint_pubkey, holder_pubkey]
+#[account]
+pub struct TransferCounter {
+    /// The mint this counter is for
+    pub mint: Pubkey,
+
+    /// The holder whose outgoing volume is tracked
+    pub holder: Pubkey,
+
+    /// Volume sent in the current daily window
+    pub daily_volume: u64,
+    pub daily_window_start: i64,
+
+    /// Volume sent in the current monthly window
+    pub monthly_volume: u64,
+    pub monthly_window_start: i64,
+
+    /// Reserved space
+    pub reserved: [u8; 32],
+}
+
+impl TransferCounter {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // holder
+        8 + // daily_volume
+        8 + // daily_window_start
+        8 + // monthly_volume
+        8 + // monthly_window_start
+        32; // reserved
+
+    /// Set up a newly created counter; existing counters are left untouched
+    pub fn initialize_if_new(&mut self, mint: Pubkey, holder: Pubkey) {
+        if self.holder == Pubkey::default() {
+            self.mint = mint;
+            self.holder = holder;
+            self.daily_volume = 0;
+            self.daily_window_start = 0;
+            self.monthly_volume = 0;
+            self.monthly_window_start = 0;
+            self.reserved = [0; 32];
+        }
+    }
+
+    /// Take over another holder's windows and volumes, e.g. on recovery
+    pub fn carry_over(&mut self, from: &TransferCounter) {
+        self.daily_volume = from.daily_volume;
+        self.daily_window_start = from.daily_window_start;
+        self.monthly_volume = from.monthly_volume;
+        self.monthly_window_start = from.monthly_window_start;
+    }
+
+    /// Volumes of the current windows, treating elapsed windows as reset
+    pub fn current_volumes(&self, limits: &TransferLimits, now: i64) -> (u64, u64) {
+        let daily = if now - self.daily_window_start >= limits.daily_window() {
+            0
+        } else {
+            self.daily_volume
+        };
+        let monthly = if now - self.monthly_window_start >= TransferLimits::MONTH {
+            0
+        } else {
+            self.monthly_volume
+        };
+        (daily, monthly)
+    }
+
+    /// Add an outgoing transfer, starting new windows where they elapsed
+    pub fn record_transfer(&mut self, limits: &TransferLimits, amount: u64, now: i64) -> Result<()> {
+        if now - self.daily_window_start >= limits.daily_window() {
+            self.daily_volume = 0;
+            self.daily_window_start = now;
+        }
+        if now - self.monthly_window_start >= TransferLimits::MONTH {
+            self.monthly_volume = 0;
+            self.monthly_window_start = now;
+        }
+
+        self.daily_volume = self.daily_volume
+            .checked_add(amount)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+        self.monthly_volume = self.monthly_volume
+            .checked_add(amount)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+
+        Ok(())
+    }
 }
 
 /// Holding limits configuration
@@ -300,6 +872,20 @@ impl HoldingLimits {
     pub const LEN: usize = (1 + 8) + // min_holding_amount
         (1 + 8) + // max_holding_amount
         (1 + 2); // max_holding_percentage
+
+    /// Validate the limits are consistent with each other
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(min), Some(max)) = (self.min_holding_amount, self.max_holding_amount) {
+            require!(min <= max, crate::error::ERC3643Error::InvalidComplianceConfiguration);
+        }
+        if let Some(percentage) = self.max_holding_percentage {
+            require!(
+                percentage > 0 && percentage <= 10_000,
+                crate::error::ERC3643Error::InvalidComplianceConfiguration
+            );
+        }
+        Ok(())
+    }
 }
 
 /// Lock period configuration
@@ -326,6 +912,28 @@ impl LockPeriod {
     pub fn is_active(&self, current_time: i64) -> bool {
         self.is_active && current_time >= self.start_time && current_time <= self.end_time
     }
+
+    /// Whether a locked user is prevented from sending
+    pub fn blocks_sending(&self) -> bool {
+        matches!(self.lock_type, LockType::FullLock | LockType::ReceiveOnly)
+    }
+
+    /// Whether a locked user is prevented from receiving
+    pub fn blocks_receiving(&self) -> bool {
+        matches!(self.lock_type, LockType::FullLock | LockType::SendOnly)
+    }
+
+    /// Validate the lock window
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.start_time < self.end_time,
+            crate::error::ERC3643Error::InvalidComplianceConfiguration
+        );
+        if let Some(user) = &self.user {
+            validate_pubkey(user)?;
+        }
+        Ok(())
+    }
 }
 
 /// Types of lock periods
@@ -354,6 +962,19 @@ impl TradingWindow {
         1 + // days_of_week
         1; // is_active
 
+    /// Validate the window bounds and day mask
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.start_time < 86_400 && self.end_time < 86_400,
+            crate::error::ERC3643Error::InvalidComplianceConfiguration
+        );
+        require!(
+            self.days_of_week != 0 && self.days_of_week < 0x80,
+            crate::error::ERC3643Error::InvalidComplianceConfiguration
+        );
+        Ok(())
+    }
+
     pub fn is_trading_allowed(&self, timestamp: i64) -> bool {
         if !self.is_active {
             return false;
@@ -381,3 +1002,334 @@ impl TradingWindow {
         }
     }
 }
+
+/// Transfer escrowed by a holder until an agent or compliance manager
+/// approves or rejects it
+/// PDA: ["transfer_request", mint_pubkey, from_pubkey, request_id]
+#[account]
+pub struct TransferRequest {
+    /// The mint being transferred
+    pub mint: Pubkey,
+
+    /// Holder that escrowed the tokens
+    pub from: Pubkey,
+
+    /// Intended recipient
+    pub to: Pubkey,
+
+    /// Escrowed amount
+    pub amount: u64,
+
+    /// Holder-chosen request identifier
+    pub request_id: u64,
+
+    /// Timestamps
+    pub created_at: i64,
+    pub expires_at: i64,
+
+    /// Reserved space
+    pub reserved: [u8; 32],
+}
+
+impl TransferRequest {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // from
+        32 + // to
+        8 + // amount
+        8 + // request_id
+        8 + // created_at
+        8 + // expires_at
+        32; // reserved
+
+    /// Whether the request can no longer be approved
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Arbitrary real-world timestamp, so fresh counters start new windows
+    const NOW: i64 = 1_700_000_000;
+
+    fn registry() -> ComplianceRegistry {
+        ComplianceRegistry {
+            mint: Pubkey::new_unique(),
+            modules: vec![],
+            country_restrictions: vec![],
+            transfer_limits: TransferLimits {
+                min_transfer_amount: None,
+                max_transfer_amount: None,
+                daily_limit: None,
+                monthly_limit: None,
+                reset_period: 0,
+            },
+            holding_limits: HoldingLimits {
+                min_holding_amount: None,
+                max_holding_amount: None,
+                max_holding_percentage: None,
+            },
+            lock_periods: vec![],
+            trading_windows: vec![],
+            max_investors: None,
+            current_investors: 0,
+            country_investor_limits: vec![],
+            require_whitelist: false,
+            enable_blacklist: false,
+            require_kyc: false,
+            require_aml: false,
+            created_at: 0,
+            updated_at: 0,
+            transfer_request_ttl: 0,
+            timelock_delay: 0,
+            queued_change_count: 0,
+            reserved: [0; 40],
+        }
+    }
+
+    fn transfer(from: Pubkey, to: Pubkey, amount: u64) -> TransferCheck {
+        TransferCheck {
+            from,
+            to,
+            amount,
+            from_balance: amount,
+            to_balance: 0,
+            supply: 1_000_000,
+            from_country: 840,
+            to_country: 840,
+        }
+    }
+
+    fn counter() -> TransferCounter {
+        TransferCounter {
+            mint: Pubkey::new_unique(),
+            holder: Pubkey::new_unique(),
+            daily_volume: 0,
+            daily_window_start: 0,
+            monthly_volume: 0,
+            monthly_window_start: 0,
+            reserved: [0; 32],
+        }
+    }
+
+    fn restriction(country_code: u16, restriction_type: RestrictionType) -> CountryRestriction {
+        CountryRestriction {
+            country_code,
+            restriction_type,
+            is_active: true,
+        }
+    }
+
+    fn lock(user: Pubkey, lock_type: LockType) -> LockPeriod {
+        LockPeriod {
+            user: Some(user),
+            start_time: 0,
+            end_time: 1_000,
+            lock_type,
+            is_active: true,
+        }
+    }
+
+    #[test]
+    fn daily_window_resets_at_boundary() {
+        let limits = TransferLimits {
+            daily_limit: Some(100),
+            ..registry().transfer_limits
+        };
+        let mut counter = counter();
+        counter.record_transfer(&limits, 60, NOW).unwrap();
+
+        let end = NOW + TransferLimits::DAY;
+        assert_eq!(counter.current_volumes(&limits, end - 1), (60, 60));
+        assert_eq!(counter.current_volumes(&limits, end), (0, 60));
+
+        counter.record_transfer(&limits, 10, end).unwrap();
+        assert_eq!(counter.daily_volume, 10);
+        assert_eq!(counter.daily_window_start, end);
+        assert_eq!(counter.monthly_volume, 70);
+    }
+
+    #[test]
+    fn monthly_window_resets_at_boundary() {
+        let limits = TransferLimits {
+            monthly_limit: Some(1_000),
+            ..registry().transfer_limits
+        };
+        let mut counter = counter();
+        counter.record_transfer(&limits, 500, NOW).unwrap();
+
+        let end = NOW + TransferLimits::MONTH;
+        assert_eq!(counter.current_volumes(&limits, end - 1).1, 500);
+        assert_eq!(counter.current_volumes(&limits, end).1, 0);
+
+        counter.record_transfer(&limits, 20, end).unwrap();
+        assert_eq!(counter.monthly_volume, 20);
+        assert_eq!(counter.monthly_window_start, end);
+    }
+
+    #[test]
+    fn daily_limit_applies_until_window_elapses() {
+        let mut compliance = registry();
+        compliance.transfer_limits.daily_limit = Some(100);
+        compliance.transfer_limits.reset_period = 3_600;
+        let mut counter = counter();
+        counter.record_transfer(&compliance.transfer_limits, 80, NOW).unwrap();
+
+        let check = transfer(Pubkey::new_unique(), Pubkey::new_unique(), 30);
+        let blocked = compliance.check_transfer_compliance_at(&check, Some(&counter), NOW + 3_599).unwrap();
+        assert!(!blocked.allowed);
+        let allowed = compliance.check_transfer_compliance_at(&check, Some(&counter), NOW + 3_600).unwrap();
+        assert!(allowed.allowed);
+    }
+
+    #[test]
+    fn investor_count_follows_zero_balance_transitions() {
+        let mut compliance = registry();
+        compliance.country_investor_limits.push(CountryInvestorLimit {
+            country_code: 840,
+            max_investors: 10,
+            current_investors: 0,
+        });
+
+        compliance.record_balance_change(840, 0, 50).unwrap();
+        assert_eq!(compliance.current_investors, 1);
+        assert_eq!(compliance.country_investor_limits[0].current_investors, 1);
+
+        // Balance changes that stay nonzero leave the count alone
+        compliance.record_balance_change(840, 50, 20).unwrap();
+        assert_eq!(compliance.current_investors, 1);
+
+        compliance.record_balance_change(840, 20, 0).unwrap();
+        assert_eq!(compliance.current_investors, 0);
+        assert_eq!(compliance.country_investor_limits[0].current_investors, 0);
+
+        // Never drops below zero
+        compliance.record_balance_change(840, 20, 0).unwrap();
+        assert_eq!(compliance.current_investors, 0);
+    }
+
+    #[test]
+    fn lock_type_direction() {
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let check = transfer(from, to, 10);
+        let cases = [
+            (from, LockType::FullLock, false),
+            (to, LockType::FullLock, false),
+            (from, LockType::ReceiveOnly, false),
+            (to, LockType::ReceiveOnly, true),
+            (from, LockType::SendOnly, true),
+            (to, LockType::SendOnly, false),
+        ];
+
+        for (user, lock_type, allowed) in cases {
+            let mut compliance = registry();
+            compliance.lock_periods.push(lock(user, lock_type.clone()));
+            let result = compliance.check_transfer_compliance_at(&check, None, 500).unwrap();
+            assert_eq!(result.allowed, allowed, "{:?}", lock_type);
+
+            // Outside the window the lock has no effect
+            let result = compliance.check_transfer_compliance_at(&check, None, 1_001).unwrap();
+            assert!(result.allowed);
+        }
+    }
+
+    #[test]
+    fn lock_does_not_block_minting_to_send_locked_user() {
+        let to = Pubkey::new_unique();
+        let mut compliance = registry();
+        compliance.lock_periods.push(lock(to, LockType::ReceiveOnly));
+
+        let check = transfer(Pubkey::default(), to, 10);
+        assert!(compliance.check_transfer_compliance_at(&check, None, 500).unwrap().allowed);
+    }
+
+    #[test]
+    fn block_list_mode_rejects_only_blocked_countries() {
+        let mut compliance = registry();
+        compliance.country_restrictions.push(restriction(408, RestrictionType::Blocked));
+        compliance.country_restrictions.push(restriction(250, RestrictionType::RequiresApproval));
+
+        assert!(!compliance.check_country_restrictions(Some(840), 276).unwrap());
+        assert!(compliance.check_country_restrictions(Some(840), 408).is_err());
+        assert!(compliance.check_country_restrictions(Some(408), 840).is_err());
+        assert!(compliance.check_country_restrictions(Some(250), 840).unwrap());
+    }
+
+    #[test]
+    fn allow_list_mode_rejects_unlisted_countries() {
+        let mut compliance = registry();
+        compliance.country_restrictions.push(restriction(840, RestrictionType::Allowed));
+        compliance.country_restrictions.push(restriction(250, RestrictionType::RequiresApproval));
+
+        assert!(!compliance.check_country_restrictions(Some(840), 840).unwrap());
+        assert!(compliance.check_country_restrictions(Some(840), 250).unwrap());
+        assert!(compliance.check_country_restrictions(Some(840), 276).is_err());
+        assert!(compliance.check_country_restrictions(Some(276), 840).is_err());
+
+        // Minting has no sender country
+        assert!(!compliance.check_country_restrictions(None, 840).unwrap());
+
+        // Inactive allow entries do not switch on allow-list mode
+        compliance.country_restrictions[0].is_active = false;
+        assert!(!compliance.check_country_restrictions(Some(276), 276).unwrap());
+    }
+
+    #[test]
+    fn approval_is_reported_only_when_other_rules_pass() {
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut compliance = registry();
+        compliance.country_restrictions.push(restriction(840, RestrictionType::RequiresApproval));
+
+        let result = compliance.check_transfer_compliance_at(&transfer(from, to, 10), None, 500).unwrap();
+        assert!(!result.allowed && result.requires_approval);
+
+        compliance.lock_periods.push(lock(from, LockType::FullLock));
+        let result = compliance.check_transfer_compliance_at(&transfer(from, to, 10), None, 500).unwrap();
+        assert!(!result.allowed && !result.requires_approval);
+    }
+
+    #[test]
+    fn holding_percentage_skips_first_issuance() {
+        let mut compliance = registry();
+        compliance.holding_limits.max_holding_percentage = Some(1_000);
+        let to = Pubkey::new_unique();
+
+        let mut mint = transfer(Pubkey::default(), to, 1_000);
+        mint.supply = 1_000;
+        assert!(compliance.check_transfer_compliance_at(&mint, None, 0).unwrap().allowed);
+
+        // Later issuance is held to the percentage of the new supply
+        mint.supply = 2_000;
+        assert!(!compliance.check_transfer_compliance_at(&mint, None, 0).unwrap().allowed);
+
+        mint.amount = 200;
+        assert!(compliance.check_transfer_compliance_at(&mint, None, 0).unwrap().allowed);
+    }
+
+    #[test]
+    fn receiver_balance_overflow_is_an_error() {
+        let compliance = registry();
+        let mut check = transfer(Pubkey::new_unique(), Pubkey::new_unique(), 1);
+        check.to_balance = u64::MAX;
+
+        assert!(compliance.check_transfer_compliance_at(&check, None, 0).is_err());
+    }
+
+    #[test]
+    fn recovered_counter_keeps_the_lost_wallet_windows() {
+        let limits = TransferLimits {
+            daily_limit: Some(100),
+            ..registry().transfer_limits
+        };
+        let mut lost = counter();
+        lost.record_transfer(&limits, 80, NOW).unwrap();
+
+        let mut recovered = counter();
+        recovered.carry_over(&lost);
+        assert_eq!(recovered.current_volumes(&limits, NOW + 60), (80, 80));
+        assert_ne!(recovered.holder, lost.holder);
+    }
+}
@@ -19,6 +19,7 @@ pub struct TokenConfig {
 
     /// Program authorities
     pub owner: Pubkey,
+    /// Unused; agent roles live in the role registry
     pub agents: Vec<Pubkey>,
 
     /// Transfer hook configuration
@@ -43,8 +44,20 @@ pub struct TokenConfig {
     pub created_at: i64,
     pub updated_at: i64,
 
+    /// Compliance manager allowed to configure compliance rules (default = none)
+    pub compliance_manager: Pubkey,
+
+    /// Hard cap on the mint supply (None = uncapped)
+    pub max_supply: Option<u64>,
+
+    /// Owner nominated by `transfer_ownership`, pending acceptance (default = none)
+    pub pending_owner: Pubkey,
+
+    /// Multisig whose quorum replaces the owner for sensitive actions (default = none)
+    pub multisig: Pubkey,
+
     /// Reserved space for future upgrades
-    pub reserved: [u8; 128],
+    pub reserved: [u8; 23],
 }
 
 impl TokenConfig {
@@ -69,41 +82,20 @@ impl TokenConfig {
         (1 + 8) + // max_holding (Option<u64>)
         8 + // created_at
         8 + // updated_at
-        128; // reserved
+        32 + // compliance_manager
+        (1 + 8) + // max_supply (Option<u64>)
+        32 + // pending_owner
+        32 + // multisig
+        23; // reserved
 
     /// Check if the given pubkey is the owner
     pub fn is_owner(&self, pubkey: &Pubkey) -> bool {
         self.owner == *pubkey
     }
 
-    /// Check if the given pubkey is an agent
-    pub fn is_agent(&self, pubkey: &Pubkey) -> bool {
-        self.agents.contains(pubkey)
-    }
-
-    /// Check if the given pubkey has agent or owner privileges
-    pub fn has_agent_or_owner_role(&self, pubkey: &Pubkey) -> bool {
-        self.is_owner(pubkey) || self.is_agent(pubkey)
-    }
-
-    /// Add an agent (only owner can call)
-    pub fn add_agent(&mut self, agent: Pubkey) -> Result<()> {
-        require!(!self.agents.contains(&agent), crate::error::ERC3643Error::InvalidArgument);
-        require!(self.agents.len() < MAX_AGENTS, crate::error::ERC3643Error::TooManyTrustedIssuers);
-        self.agents.push(agent);
-        self.updated_at = Clock::get()?.unix_timestamp;
-        Ok(())
-    }
-
-    /// Remove an agent (only owner can call)
-    pub fn remove_agent(&mut self, agent: Pubkey) -> Result<()> {
-        if let Some(pos) = self.agents.iter().position(|&x| x == agent) {
-            self.agents.remove(pos);
-            self.updated_at = Clock::get()?.unix_timestamp;
-            Ok(())
-        } else {
-            Err(crate::error::ERC3643Error::InvalidArgument.into())
-        }
+    /// Check if sensitive owner actions must go through the multisig
+    pub fn has_multisig(&self) -> bool {
+        self.multisig != Pubkey::default()
     }
 
     /// Update token metadata
@@ -122,6 +114,16 @@ impl TokenConfig {
         Ok(())
     }
 
+    /// Set the supply cap, which cannot be below the current supply
+    pub fn set_max_supply(&mut self, max_supply: Option<u64>, current_supply: u64) -> Result<()> {
+        if let Some(max) = max_supply {
+            require!(max >= current_supply, crate::error::ERC3643Error::MaxSupplyExceeded);
+        }
+        self.max_supply = max_supply;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
     /// Set pause status
     pub fn set_paused(&mut self, paused: bool) -> Result<()> {
         self.paused = paused;
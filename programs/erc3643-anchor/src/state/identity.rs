@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
 use super::*;
 
-/// Identity registry account for a user
-/// PDA: ["identity", user_pubkey]
+/// Per-token identity registry entry for a user, bound to the user's
+/// shared `IdentityStorage`
+/// PDA: ["identity", mint_pubkey, user_pubkey]
 #[account]
 pub struct IdentityRegistry {
     /// The user this identity is for
@@ -28,9 +29,19 @@ pub struct IdentityRegistry {
     
     /// Agent who registered this identity
     pub registered_by: Pubkey,
+
+    /// Identity key claims are issued against (the original wallet, carried
+    /// over to the new wallet on recovery)
+    pub identity: Pubkey,
+
+    /// The mint this registry entry belongs to
+    pub mint: Pubkey,
+
+    /// The identity storage this registry entry is bound to
+    pub storage: Pubkey,
     
     /// Reserved space
-    pub reserved: [u8; 64],
+    pub reserved: [u8; 32],
 }
 
 impl IdentityRegistry {
@@ -45,7 +56,10 @@ impl IdentityRegistry {
         8 + // last_verified_at
         8 + // updated_at
         32 + // registered_by
-        64; // reserved
+        32 + // identity
+        32 + // mint
+        32 + // storage
+        32; // reserved
 
     /// Update verification status
     pub fn update_verification(&mut self, verified: bool) -> Result<()> {
@@ -85,6 +99,67 @@ impl IdentityRegistry {
     }
 }
 
+/// Identity storage shared by every token a user is registered for
+/// PDA: ["identity_storage", user_pubkey]
+#[account]
+pub struct IdentityStorage {
+    /// The user this storage is for
+    pub user: Pubkey,
+
+    /// Identity key claims are issued against
+    pub identity: Pubkey,
+
+    /// Country code (ISO 3166-1 numeric) used for new token registrations
+    pub country: u16,
+
+    /// OnchainID address (if using external identity)
+    pub onchain_id: Option<Pubkey>,
+
+    /// Number of token identity registries bound to this storage
+    pub bound_registries: u32,
+
+    /// Timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+
+    /// Agent who created this storage
+    pub created_by: Pubkey,
+
+    /// Reserved space
+    pub reserved: [u8; 32],
+}
+
+impl IdentityStorage {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // user
+        32 + // identity
+        2 + // country
+        (1 + 32) + // onchain_id (Option<Pubkey>)
+        4 + // bound_registries
+        8 + // created_at
+        8 + // updated_at
+        32 + // created_by
+        32; // reserved
+
+    /// Record a token identity registry binding to this storage
+    pub fn bind(&mut self) -> Result<()> {
+        self.bound_registries = self.bound_registries
+            .checked_add(1)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Remove a token identity registry binding from this storage
+    pub fn unbind(&mut self) -> Result<()> {
+        self.bound_registries = self.bound_registries
+            .checked_sub(1)
+            .ok_or(crate::error::ERC3643Error::ArithmeticUnderflow)?;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+}
+
 /// Trusted issuers registry
 /// PDA: ["trusted_issuers", mint_pubkey]
 #[account]
@@ -2,11 +2,17 @@ pub mod config;
 pub mod identity;
 pub mod compliance;
 pub mod claims;
+pub mod roles;
+pub mod multisig;
+pub mod timelock;
 
 pub use config::*;
 pub use identity::*;
 pub use compliance::*;
 pub use claims::*;
+pub use roles::*;
+pub use multisig::*;
+pub use timelock::*;
 
 use anchor_lang::prelude::*;
 
@@ -19,6 +25,12 @@ pub const MAX_CLAIM_TOPICS: usize = 15;
 /// Maximum number of agents allowed
 pub const MAX_AGENTS: usize = 10;
 
+/// Maximum number of multisig approvers
+pub const MAX_MULTISIG_APPROVERS: usize = 10;
+
+/// Maximum number of members in a role registry
+pub const MAX_ROLE_MEMBERS: usize = 20;
+
 /// Maximum length for token name
 pub const MAX_NAME_LENGTH: usize = 32;
 
@@ -31,29 +43,78 @@ pub const MAX_URI_LENGTH: usize = 200;
 /// Maximum size for claim data
 pub const MAX_CLAIM_DATA_SIZE: usize = 1024;
 
-/// Maximum size for claim signature
-pub const MAX_CLAIM_SIGNATURE_SIZE: usize = 64;
+/// Maximum size for claim signature (65 bytes for recoverable ECDSA signatures)
+pub const MAX_CLAIM_SIGNATURE_SIZE: usize = 65;
+
+/// Maximum number of country restrictions per compliance registry
+pub const MAX_COUNTRY_RESTRICTIONS: usize = 50;
+
+/// Maximum number of items in a batch instruction
+pub const MAX_BATCH_SIZE: usize = 20;
+
+/// Maximum number of compliance modules per compliance registry
+pub const MAX_COMPLIANCE_MODULES: usize = 10;
+
+/// Maximum number of per-country investor caps per compliance registry
+pub const MAX_COUNTRY_INVESTOR_LIMITS: usize = 20;
+
+/// Maximum number of lock periods per compliance registry
+pub const MAX_LOCK_PERIODS: usize = 10;
+
+/// Maximum number of trading windows per compliance registry
+pub const MAX_TRADING_WINDOWS: usize = 10;
 
 /// Current version of the token implementation
 pub const TOKEN_VERSION: &str = "1.0.0";
 
+/// TokenMetadata additional field mirroring the onchain ID
+pub const ONCHAIN_ID_METADATA_KEY: &str = "onchain_id";
+
 /// Seeds for PDA derivation
 pub const CONFIG_SEED: &[u8] = b"config";
 pub const IDENTITY_SEED: &[u8] = b"identity";
+pub const IDENTITY_STORAGE_SEED: &[u8] = b"identity_storage";
+pub const TRANSFER_COUNTER_SEED: &[u8] = b"transfer_counter";
+pub const TRANSFER_REQUEST_SEED: &[u8] = b"transfer_request";
 pub const TRUSTED_ISSUERS_SEED: &[u8] = b"trusted_issuers";
 pub const CLAIM_SEED: &[u8] = b"claim";
 pub const COMPLIANCE_SEED: &[u8] = b"compliance";
 pub const CLAIM_TOPICS_SEED: &[u8] = b"claim_topics";
+pub const ROLES_SEED: &[u8] = b"roles";
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const COMPLIANCE_CHANGE_SEED: &[u8] = b"compliance_change";
+pub const MODULE_AUTHORITY_SEED: &[u8] = b"module_authority";
 
-/// Role definitions
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+/// Capabilities that can be granted through the role registry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Role {
-    Owner,
-    Agent,
-    Issuer,
+    /// Mint and burn tokens
+    SupplyAgent,
+    /// Freeze addresses and balances, thaw accounts and pause the token
+    FreezeAgent,
+    /// Force transfers and decide on transfer requests
+    TransferAgent,
+    /// Move the holdings of a lost wallet to a new one
+    RecoveryAgent,
+    /// Register, bind, update and delete identities
+    IdentityAgent,
+    /// Configure compliance rules and modules
     ComplianceManager,
 }
 
+impl Role {
+    /// Bit of this role in a role bitset
+    pub fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+
+    /// Whether the role is one of the agent capabilities
+    pub fn is_agent_role(&self) -> bool {
+        *self != Role::ComplianceManager
+    }
+}
+
 /// Enforcement mode for transfers
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
 pub enum EnforcementMode {
@@ -74,6 +135,26 @@ pub enum ClaimScheme {
     RSA = 2,
     /// Contract-based claim
     Contract = 3,
+    /// Ed25519 signature scheme
+    Ed25519 = 4,
+}
+
+impl ClaimScheme {
+    /// Parse a claim scheme from its numeric identifier
+    pub fn from_u64(scheme: u64) -> Result<Self> {
+        match scheme {
+            1 => Ok(ClaimScheme::ECDSA),
+            2 => Ok(ClaimScheme::RSA),
+            3 => Ok(ClaimScheme::Contract),
+            4 => Ok(ClaimScheme::Ed25519),
+            _ => Err(crate::error::ERC3643Error::InvalidArgument.into()),
+        }
+    }
+
+    /// Numeric identifier of the claim scheme
+    pub fn to_u64(&self) -> u64 {
+        self.clone() as u64
+    }
 }
 
 /// Compliance check result
@@ -82,6 +163,8 @@ pub struct ComplianceResult {
     pub allowed: bool,
     pub reason: String,
     pub module: String,
+    /// Denied only because the transfer requires issuer approval
+    pub requires_approval: bool,
 }
 
 /// Transfer validation context
@@ -103,6 +186,22 @@ pub struct BatchResult {
     pub failures: Vec<String>,
 }
 
+impl BatchResult {
+    pub fn new(total: usize) -> Self {
+        Self {
+            total: total as u32,
+            success: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Record a skipped item. Reasons are kept short since the result is
+    /// returned through the 1 KiB return data.
+    pub fn record_failure(&mut self, index: usize, reason: &str) {
+        self.failures.push(format!("{}: {}", index, reason));
+    }
+}
+
 /// Utility functions for account validation
 pub fn validate_pubkey(pubkey: &Pubkey) -> Result<()> {
     require!(*pubkey != Pubkey::default(), crate::error::ERC3643Error::ZeroAddressNotAllowed);
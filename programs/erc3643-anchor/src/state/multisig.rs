@@ -0,0 +1,391 @@
+use anchor_lang::prelude::*;
+use super::*;
+
+/// M-of-N approvers that replace the single-signer owner for sensitive
+/// admin actions
+/// PDA: ["multisig", mint_pubkey]
+#[account]
+pub struct Multisig {
+    /// The mint this multisig is for
+    pub mint: Pubkey,
+
+    /// Keys allowed to propose, approve and execute
+    pub approvers: Vec<Pubkey>,
+
+    /// Approvals required to execute a proposal
+    pub threshold: u8,
+
+    /// Seconds a proposal stays open for approval and execution
+    pub proposal_ttl: i64,
+
+    /// Id assigned to the next proposal
+    pub proposal_count: u64,
+
+    /// Timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+
+    /// Reserved space
+    pub reserved: [u8; 32],
+}
+
+impl Multisig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        (4 + MAX_MULTISIG_APPROVERS * 32) + // approvers
+        1 + // threshold
+        8 + // proposal_ttl
+        8 + // proposal_count
+        8 + // created_at
+        8 + // updated_at
+        32; // reserved
+
+    /// Check if the given pubkey is an approver
+    pub fn is_approver(&self, pubkey: &Pubkey) -> bool {
+        self.approvers.contains(pubkey)
+    }
+
+    /// Replace the approver set and threshold
+    pub fn set_approvers(&mut self, approvers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        validate_approvers(&approvers, threshold)?;
+        self.approvers = approvers;
+        self.threshold = threshold;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Approvals on a proposal that come from current approvers
+    pub fn approval_count(&self, proposal: &Proposal) -> usize {
+        proposal
+            .approvals
+            .iter()
+            .filter(|approver| self.is_approver(approver))
+            .count()
+    }
+
+    /// Open a proposal under the next id, approved by its proposer.
+    /// The caller checks that the proposer is an approver.
+    pub fn propose(
+        &mut self,
+        proposal: &mut Proposal,
+        mint: Pubkey,
+        proposer: Pubkey,
+        action: AdminAction,
+        now: i64,
+    ) -> Result<()> {
+        action.validate()?;
+
+        proposal.mint = mint;
+        proposal.id = self.proposal_count;
+        proposal.proposer = proposer;
+        proposal.action = action;
+        proposal.approvals = vec![proposer];
+        proposal.created_at = now;
+        proposal.expires_at = now
+            .checked_add(self.proposal_ttl)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+
+        self.proposal_count = self
+            .proposal_count
+            .checked_add(1)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Record an approval on an open proposal. The caller checks that the
+    /// approver is a current approver.
+    pub fn approve(&self, proposal: &mut Proposal, approver: Pubkey, now: i64) -> Result<()> {
+        require!(!proposal.is_expired(now), crate::error::ERC3643Error::ProposalExpired);
+
+        // Approvals from rotated-out approvers no longer count
+        proposal.approvals.retain(|a| self.is_approver(a));
+        proposal.approve(approver)
+    }
+
+    /// Check that an open proposal reached the threshold
+    pub fn check_executable(&self, proposal: &Proposal, now: i64) -> Result<()> {
+        require!(!proposal.is_expired(now), crate::error::ERC3643Error::ProposalExpired);
+        require!(
+            self.approval_count(proposal) >= self.threshold as usize,
+            crate::error::ERC3643Error::ProposalThresholdNotMet
+        );
+        Ok(())
+    }
+}
+
+/// Check an approver set and its threshold
+pub fn validate_approvers(approvers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(approvers.len() <= MAX_MULTISIG_APPROVERS, crate::error::ERC3643Error::TooManyApprovers);
+    require!(
+        threshold > 0 && threshold as usize <= approvers.len(),
+        crate::error::ERC3643Error::InvalidMultisigThreshold
+    );
+    for (i, approver) in approvers.iter().enumerate() {
+        require!(*approver != Pubkey::default(), crate::error::ERC3643Error::InvalidArgument);
+        require!(!approvers[..i].contains(approver), crate::error::ERC3643Error::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Admin action held by a proposal until quorum is reached
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum AdminAction {
+    AddAgent { agent: Pubkey, role: Role },
+    RemoveAgent { agent: Pubkey, role: Role },
+    AddTrustedIssuer { issuer: Pubkey, claim_topics: Vec<u64> },
+    SetPauseStatus { paused: bool },
+    TransferOwnership { new_owner: Pubkey },
+    SetApprovers { approvers: Vec<Pubkey>, threshold: u8 },
+    RemoveTrustedIssuer { issuer: Pubkey },
+    AddClaimTopic { topic: u64 },
+    RemoveClaimTopic { topic: u64 },
+    GrantRole { member: Pubkey, role: Role },
+    RevokeRole { member: Pubkey, role: Role },
+    SetComplianceManager { compliance_manager: Pubkey },
+    SetMaxSupply { max_supply: Option<u64> },
+    CancelOwnershipTransfer,
+    SetTransferHook { hook_program: Pubkey },
+    SetEnforcementMode { mode: EnforcementMode },
+    SetComplianceTimelock { delay: i64 },
+    CancelComplianceChange { id: u64 },
+    SetTokenInformation { name: Option<String>, symbol: Option<String>, onchain_id: Option<Pubkey> },
+}
+
+impl AdminAction {
+    /// Size of the largest variant (SetApprovers)
+    pub const LEN: usize = 1 + // variant
+        (4 + MAX_MULTISIG_APPROVERS * 32) + // approvers
+        1; // threshold
+
+    /// Check that the action fits the proposal account
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            AdminAction::AddTrustedIssuer { claim_topics, .. } => {
+                require!(claim_topics.len() <= MAX_CLAIM_TOPICS, crate::error::ERC3643Error::TooManyClaimTopics);
+            }
+            AdminAction::SetApprovers { approvers, threshold } => {
+                validate_approvers(approvers, *threshold)?;
+            }
+            AdminAction::SetTokenInformation { name, symbol, .. } => {
+                if let Some(name) = name {
+                    validate_string_length(name, MAX_NAME_LENGTH, crate::error::ERC3643Error::NameTooLong)?;
+                }
+                if let Some(symbol) = symbol {
+                    validate_string_length(symbol, MAX_SYMBOL_LENGTH, crate::error::ERC3643Error::SymbolTooLong)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Proposal to run an admin action once the multisig threshold is met
+/// PDA: ["proposal", mint_pubkey, id (u64 little endian)]
+#[account]
+pub struct Proposal {
+    /// The mint this proposal is for
+    pub mint: Pubkey,
+
+    /// Sequential id within the multisig
+    pub id: u64,
+
+    /// Approver who created the proposal, refunded on close
+    pub proposer: Pubkey,
+
+    /// Action to execute
+    pub action: AdminAction,
+
+    /// Approvers who approved the proposal
+    pub approvals: Vec<Pubkey>,
+
+    /// Timestamps
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        8 + // id
+        32 + // proposer
+        AdminAction::LEN + // action
+        (4 + MAX_MULTISIG_APPROVERS * 32) + // approvals
+        8 + // created_at
+        8; // expires_at
+
+    /// Check if the proposal can no longer be approved or executed
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Check if the authority may cancel: the proposer at any time, anyone
+    /// once the proposal expired
+    pub fn can_cancel(&self, authority: &Pubkey, now: i64) -> bool {
+        *authority == self.proposer || self.is_expired(now)
+    }
+
+    /// Record an approval
+    pub fn approve(&mut self, approver: Pubkey) -> Result<()> {
+        require!(!self.approvals.contains(&approver), crate::error::ERC3643Error::ProposalAlreadyApproved);
+        require!(self.approvals.len() < MAX_MULTISIG_APPROVERS, crate::error::ERC3643Error::TooManyApprovers);
+        self.approvals.push(approver);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const TTL: i64 = 86_400;
+
+    fn multisig(approvers: &[Pubkey], threshold: u8) -> Multisig {
+        Multisig {
+            mint: Pubkey::new_unique(),
+            approvers: approvers.to_vec(),
+            threshold,
+            proposal_ttl: TTL,
+            proposal_count: 0,
+            created_at: 0,
+            updated_at: 0,
+            reserved: [0; 32],
+        }
+    }
+
+    fn proposal() -> Proposal {
+        Proposal {
+            mint: Pubkey::default(),
+            id: 0,
+            proposer: Pubkey::default(),
+            action: AdminAction::CancelOwnershipTransfer,
+            approvals: vec![],
+            created_at: 0,
+            expires_at: 0,
+        }
+    }
+
+    fn approvers(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn threshold_must_fit_the_approver_set() {
+        let keys = approvers(3);
+        assert!(validate_approvers(&keys, 1).is_ok());
+        assert!(validate_approvers(&keys, 3).is_ok());
+        assert!(validate_approvers(&keys, 0).is_err());
+        assert!(validate_approvers(&keys, 4).is_err());
+        assert!(validate_approvers(&approvers(MAX_MULTISIG_APPROVERS + 1), 1).is_err());
+        assert!(validate_approvers(&[keys[0], keys[0]], 1).is_err());
+        assert!(validate_approvers(&[keys[0], Pubkey::default()], 1).is_err());
+    }
+
+    #[test]
+    fn proposals_take_sequential_ids_and_the_proposer_approval() {
+        let keys = approvers(3);
+        let mut multisig = multisig(&keys, 2);
+        let mint = multisig.mint;
+
+        let mut first = proposal();
+        multisig
+            .propose(&mut first, mint, keys[0], AdminAction::SetPauseStatus { paused: true }, NOW)
+            .unwrap();
+        let mut second = proposal();
+        multisig
+            .propose(&mut second, mint, keys[1], AdminAction::CancelOwnershipTransfer, NOW)
+            .unwrap();
+
+        assert_eq!((first.id, second.id), (0, 1));
+        assert_eq!(multisig.proposal_count, 2);
+        assert_eq!(first.approvals, vec![keys[0]]);
+        assert_eq!(first.expires_at, NOW + TTL);
+
+        let invalid = AdminAction::SetApprovers { approvers: keys.clone(), threshold: 4 };
+        assert!(multisig.propose(&mut proposal(), mint, keys[0], invalid, NOW).is_err());
+        assert_eq!(multisig.proposal_count, 2);
+    }
+
+    #[test]
+    fn execution_waits_for_the_threshold() {
+        let keys = approvers(3);
+        let mut multisig = multisig(&keys, 2);
+        let mut proposal = proposal();
+        let mint = multisig.mint;
+        multisig
+            .propose(&mut proposal, mint, keys[0], AdminAction::SetPauseStatus { paused: true }, NOW)
+            .unwrap();
+        assert!(multisig.check_executable(&proposal, NOW).is_err());
+
+        multisig.approve(&mut proposal, keys[1], NOW + 1).unwrap();
+        assert!(multisig.approve(&mut proposal, keys[1], NOW + 1).is_err());
+        assert!(multisig.check_executable(&proposal, NOW + 1).is_ok());
+    }
+
+    #[test]
+    fn expired_proposals_cannot_be_approved_or_executed() {
+        let keys = approvers(2);
+        let mut multisig = multisig(&keys, 1);
+        let mut proposal = proposal();
+        let mint = multisig.mint;
+        multisig
+            .propose(&mut proposal, mint, keys[0], AdminAction::SetPauseStatus { paused: true }, NOW)
+            .unwrap();
+
+        assert!(multisig.check_executable(&proposal, NOW + TTL - 1).is_ok());
+        assert!(multisig.check_executable(&proposal, NOW + TTL).is_err());
+        assert!(multisig.approve(&mut proposal, keys[1], NOW + TTL).is_err());
+    }
+
+    #[test]
+    fn approvals_from_rotated_out_approvers_stop_counting() {
+        let keys = approvers(3);
+        let mut multisig = multisig(&keys, 2);
+        let mut proposal = proposal();
+        let mint = multisig.mint;
+        multisig
+            .propose(&mut proposal, mint, keys[0], AdminAction::SetPauseStatus { paused: true }, NOW)
+            .unwrap();
+        multisig.approve(&mut proposal, keys[1], NOW).unwrap();
+        assert_eq!(multisig.approval_count(&proposal), 2);
+
+        let replacement = Pubkey::new_unique();
+        multisig.approvers = vec![keys[0], keys[2], replacement];
+        assert_eq!(multisig.approval_count(&proposal), 1);
+        assert!(multisig.check_executable(&proposal, NOW).is_err());
+
+        multisig.approve(&mut proposal, replacement, NOW).unwrap();
+        assert_eq!(proposal.approvals, vec![keys[0], replacement]);
+        assert!(multisig.check_executable(&proposal, NOW).is_ok());
+    }
+
+    #[test]
+    fn only_the_proposer_cancels_before_expiry() {
+        let keys = approvers(2);
+        let mut multisig = multisig(&keys, 2);
+        let mut proposal = proposal();
+        let mint = multisig.mint;
+        multisig
+            .propose(&mut proposal, mint, keys[0], AdminAction::SetPauseStatus { paused: true }, NOW)
+            .unwrap();
+
+        assert!(proposal.can_cancel(&keys[0], NOW));
+        assert!(!proposal.can_cancel(&keys[1], NOW));
+        assert!(proposal.can_cancel(&keys[1], NOW + TTL));
+    }
+
+    #[test]
+    fn token_information_action_is_bounded() {
+        let action = |name: &str| AdminAction::SetTokenInformation {
+            name: Some(name.to_string()),
+            symbol: None,
+            onchain_id: Some(Pubkey::new_unique()),
+        };
+        assert!(action(&"n".repeat(MAX_NAME_LENGTH)).validate().is_ok());
+        assert!(action(&"n".repeat(MAX_NAME_LENGTH + 1)).validate().is_err());
+
+        let largest = action(&"n".repeat(MAX_NAME_LENGTH));
+        assert!(largest.try_to_vec().unwrap().len() <= AdminAction::LEN);
+    }
+}
@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+use super::*;
+
+/// Role registry mapping members to the capabilities they hold
+/// PDA: ["roles", mint_pubkey]
+///
+/// Until a multisig is configured the owner implicitly holds every role;
+/// everyone else needs the role granted in the registry.
+#[account]
+pub struct RoleRegistry {
+    /// The mint this registry is for
+    pub mint: Pubkey,
+
+    /// Members and their role bitsets
+    pub members: Vec<RoleMember>,
+
+    /// Timestamps
+    pub created_at: i64,
+    pub updated_at: i64,
+
+    /// Reserved space
+    pub reserved: [u8; 32],
+}
+
+impl RoleRegistry {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        (4 + MAX_ROLE_MEMBERS * RoleMember::LEN) + // members
+        8 + // created_at
+        8 + // updated_at
+        32; // reserved
+
+    /// Role bitset held by a member in the registry
+    pub fn roles_of(&self, member: &Pubkey) -> u8 {
+        self.members
+            .iter()
+            .find(|m| m.member == *member)
+            .map(|m| m.roles)
+            .unwrap_or(0)
+    }
+
+    /// Check if the given pubkey holds a role
+    pub fn has_role(&self, config: &TokenConfig, member: &Pubkey, role: Role) -> bool {
+        (config.is_owner(member) && !config.has_multisig()) || self.roles_of(member) & role.bit() != 0
+    }
+
+    /// Grant a role to a member
+    pub fn grant(&mut self, member: Pubkey, role: Role) -> Result<()> {
+        self.grant_at(member, role, Clock::get()?.unix_timestamp)
+    }
+
+    /// Grant a role to a member at the given time
+    pub fn grant_at(&mut self, member: Pubkey, role: Role, now: i64) -> Result<()> {
+        require!(member != Pubkey::default(), crate::error::ERC3643Error::InvalidArgument);
+
+        if let Some(entry) = self.members.iter_mut().find(|m| m.member == member) {
+            require!(entry.roles & role.bit() == 0, crate::error::ERC3643Error::RoleAlreadyGranted);
+            entry.roles |= role.bit();
+        } else {
+            require!(self.members.len() < MAX_ROLE_MEMBERS, crate::error::ERC3643Error::TooManyRoleMembers);
+            self.members.push(RoleMember {
+                member,
+                roles: role.bit(),
+            });
+        }
+
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Revoke a role from a member, dropping members left without roles
+    pub fn revoke(&mut self, member: Pubkey, role: Role) -> Result<()> {
+        self.revoke_at(member, role, Clock::get()?.unix_timestamp)
+    }
+
+    /// Revoke a role from a member at the given time
+    pub fn revoke_at(&mut self, member: Pubkey, role: Role, now: i64) -> Result<()> {
+        let pos = self
+            .members
+            .iter()
+            .position(|m| m.member == member && m.roles & role.bit() != 0)
+            .ok_or(crate::error::ERC3643Error::RoleNotGranted)?;
+
+        self.members[pos].roles &= !role.bit();
+        if self.members[pos].roles == 0 {
+            self.members.remove(pos);
+        }
+
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+/// A member of the role registry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RoleMember {
+    pub member: Pubkey,
+    /// Bitset of `Role::bit` values
+    pub roles: u8,
+}
+
+impl RoleMember {
+    pub const LEN: usize = 32 + // member
+        1; // roles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn config(owner: Pubkey) -> TokenConfig {
+        TokenConfig {
+            mint: Pubkey::new_unique(),
+            name: String::new(),
+            symbol: String::new(),
+            decimals: 0,
+            version: String::new(),
+            onchain_id: Pubkey::default(),
+            owner,
+            agents: vec![],
+            transfer_hook_program: None,
+            enforcement_mode: EnforcementMode::TransferHook,
+            paused: false,
+            total_supply: 0,
+            require_identity_verification: false,
+            allow_forced_transfers: false,
+            enable_recovery: false,
+            max_holders: None,
+            min_holding: None,
+            max_holding: None,
+            created_at: 0,
+            updated_at: 0,
+            compliance_manager: Pubkey::default(),
+            max_supply: None,
+            pending_owner: Pubkey::default(),
+            multisig: Pubkey::default(),
+            reserved: [0; 23],
+        }
+    }
+
+    fn registry() -> RoleRegistry {
+        RoleRegistry {
+            mint: Pubkey::new_unique(),
+            members: vec![],
+            created_at: 0,
+            updated_at: 0,
+            reserved: [0; 32],
+        }
+    }
+
+    #[test]
+    fn owner_holds_every_role_until_a_multisig_exists() {
+        let owner = Pubkey::new_unique();
+        let mut config = config(owner);
+        let roles = registry();
+        assert!(roles.has_role(&config, &owner, Role::SupplyAgent));
+        assert!(roles.has_role(&config, &owner, Role::ComplianceManager));
+        assert!(!roles.has_role(&config, &Pubkey::new_unique(), Role::SupplyAgent));
+
+        config.multisig = Pubkey::new_unique();
+        assert!(!roles.has_role(&config, &owner, Role::SupplyAgent));
+        assert!(!roles.has_role(&config, &owner, Role::ComplianceManager));
+    }
+
+    #[test]
+    fn granted_roles_survive_the_multisig() {
+        let owner = Pubkey::new_unique();
+        let mut config = config(owner);
+        let mut roles = registry();
+        roles.grant_at(owner, Role::FreezeAgent, NOW).unwrap();
+
+        config.multisig = Pubkey::new_unique();
+        assert!(roles.has_role(&config, &owner, Role::FreezeAgent));
+        assert!(!roles.has_role(&config, &owner, Role::SupplyAgent));
+    }
+
+    #[test]
+    fn grant_and_revoke_track_each_role_bit() {
+        let config = config(Pubkey::new_unique());
+        let agent = Pubkey::new_unique();
+        let mut roles = registry();
+
+        roles.grant_at(agent, Role::SupplyAgent, NOW).unwrap();
+        roles.grant_at(agent, Role::TransferAgent, NOW).unwrap();
+        assert!(roles.grant_at(agent, Role::SupplyAgent, NOW).is_err());
+        assert!(roles.grant_at(Pubkey::default(), Role::SupplyAgent, NOW).is_err());
+        assert_eq!(roles.members.len(), 1);
+        assert_eq!(roles.updated_at, NOW);
+
+        roles.revoke_at(agent, Role::SupplyAgent, NOW + 1).unwrap();
+        assert!(!roles.has_role(&config, &agent, Role::SupplyAgent));
+        assert!(roles.has_role(&config, &agent, Role::TransferAgent));
+        assert!(roles.revoke_at(agent, Role::SupplyAgent, NOW + 1).is_err());
+
+        roles.revoke_at(agent, Role::TransferAgent, NOW + 1).unwrap();
+        assert!(roles.members.is_empty());
+    }
+
+    #[test]
+    fn grant_is_bounded_by_the_member_limit() {
+        let mut roles = registry();
+        for _ in 0..MAX_ROLE_MEMBERS {
+            roles.grant_at(Pubkey::new_unique(), Role::IdentityAgent, NOW).unwrap();
+        }
+        assert!(roles.grant_at(Pubkey::new_unique(), Role::IdentityAgent, NOW).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use super::*;
+
+/// Compliance rule change, applied right away or queued behind the
+/// compliance timelock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum ComplianceAction {
+    AddLockPeriod { lock_period: LockPeriod },
+    UpdateLockPeriod { index: u8, lock_period: LockPeriod },
+    RemoveLockPeriod { index: u8 },
+    AddCountryRestriction { restriction: CountryRestriction },
+    RemoveCountryRestriction { country_code: u16 },
+    SetTransferLimits { limits: TransferLimits },
+    SetHoldingLimits { limits: HoldingLimits },
+    AddTradingWindow { window: TradingWindow },
+    RemoveTradingWindow { index: u8 },
+    SetMaxInvestors { max_investors: Option<u32> },
+    SetTransferRequestTtl { ttl: i64 },
+    SetCountryInvestorLimit { country_code: u16, max_investors: Option<u32> },
+    AddComplianceModule {
+        name: String,
+        module_type: ComplianceModuleType,
+        program_id: Pubkey,
+        account_count: u8,
+        parameters: Vec<u8>,
+    },
+    RemoveComplianceModule { module_id: u32 },
+    SetTimelockDelay { delay: i64 },
+}
+
+impl ComplianceAction {
+    /// Size of the largest variant (AddComplianceModule, bounded by the module size)
+    pub const LEN: usize = 1 + // variant
+        ComplianceModule::LEN; // module fields
+
+    /// Check that the action fits a queued change account
+    pub fn validate(&self) -> Result<()> {
+        if let ComplianceAction::AddComplianceModule { name, parameters, .. } = self {
+            require!(
+                !name.is_empty() && name.len() <= 32 && parameters.len() <= 256,
+                crate::error::ERC3643Error::InvalidComplianceModule
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Compliance change waiting for the timelock to elapse
+/// PDA: ["compliance_change", mint_pubkey, id (u64 little endian)]
+#[account]
+pub struct QueuedComplianceChange {
+    /// The mint this change is for
+    pub mint: Pubkey,
+
+    /// Sequential id within the compliance registry
+    pub id: u64,
+
+    /// Change to apply
+    pub action: ComplianceAction,
+
+    /// Who queued the change, refunded on close
+    pub queued_by: Pubkey,
+
+    /// Earliest time the change can be executed
+    pub eta: i64,
+
+    /// Timestamps
+    pub created_at: i64,
+}
+
+impl QueuedComplianceChange {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        8 + // id
+        ComplianceAction::LEN + // action
+        32 + // queued_by
+        8 + // eta
+        8; // created_at
+
+    /// Queue an action under the registry's next id, executable once the
+    /// registry's delay elapsed
+    pub fn queue(
+        &mut self,
+        compliance: &mut ComplianceRegistry,
+        mint: Pubkey,
+        action: ComplianceAction,
+        queued_by: Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        require!(compliance.has_timelock(), crate::error::ERC3643Error::TimelockNotEnabled);
+        action.validate()?;
+
+        self.mint = mint;
+        self.id = compliance.queued_change_count;
+        self.action = action;
+        self.queued_by = queued_by;
+        self.eta = now
+            .checked_add(compliance.timelock_delay)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+        self.created_at = now;
+
+        compliance.queued_change_count = compliance
+            .queued_change_count
+            .checked_add(1)
+            .ok_or(crate::error::ERC3643Error::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Check if the timelock elapsed
+    pub fn is_ready(&self, now: i64) -> bool {
+        now >= self.eta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const DELAY: i64 = 86_400;
+
+    fn registry(timelock_delay: i64) -> ComplianceRegistry {
+        ComplianceRegistry {
+            mint: Pubkey::new_unique(),
+            modules: vec![],
+            country_restrictions: vec![],
+            transfer_limits: TransferLimits {
+                min_transfer_amount: None,
+                max_transfer_amount: None,
+                daily_limit: None,
+                monthly_limit: None,
+                reset_period: 0,
+            },
+            holding_limits: HoldingLimits {
+                min_holding_amount: None,
+                max_holding_amount: None,
+                max_holding_percentage: None,
+            },
+            lock_periods: vec![],
+            trading_windows: vec![],
+            max_investors: None,
+            current_investors: 0,
+            country_investor_limits: vec![],
+            require_whitelist: false,
+            enable_blacklist: false,
+            require_kyc: false,
+            require_aml: false,
+            created_at: 0,
+            updated_at: 0,
+            transfer_request_ttl: 0,
+            timelock_delay,
+            queued_change_count: 0,
+            reserved: [0; 40],
+        }
+    }
+
+    fn change() -> QueuedComplianceChange {
+        QueuedComplianceChange {
+            mint: Pubkey::default(),
+            id: 0,
+            action: ComplianceAction::RemoveTradingWindow { index: 0 },
+            queued_by: Pubkey::default(),
+            eta: 0,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn queue_requires_an_enabled_timelock() {
+        let mut compliance = registry(0);
+        let action = ComplianceAction::SetMaxInvestors { max_investors: Some(10) };
+        let mint = compliance.mint;
+        assert!(change()
+            .queue(&mut compliance, mint, action, Pubkey::new_unique(), NOW)
+            .is_err());
+        assert_eq!(compliance.queued_change_count, 0);
+    }
+
+    #[test]
+    fn queued_change_executes_once_the_delay_elapsed() {
+        let mut compliance = registry(DELAY);
+        let queued_by = Pubkey::new_unique();
+        let action = ComplianceAction::SetTransferRequestTtl { ttl: 3_600 };
+        let mint = compliance.mint;
+
+        let mut change = change();
+        change.queue(&mut compliance, mint, action, queued_by, NOW).unwrap();
+        assert_eq!(change.queued_by, queued_by);
+        assert_eq!(change.eta, NOW + DELAY);
+        assert!(!change.is_ready(NOW + DELAY - 1));
+        assert!(change.is_ready(NOW + DELAY));
+    }
+
+    #[test]
+    fn cancelled_ids_are_not_reused() {
+        let mut compliance = registry(DELAY);
+        let mint = compliance.mint;
+        let queued_by = Pubkey::new_unique();
+
+        // Cancelling closes the change account; the registry counter stays put
+        change()
+            .queue(&mut compliance, mint, ComplianceAction::RemoveTradingWindow { index: 0 }, queued_by, NOW)
+            .unwrap();
+
+        let mut next = change();
+        next.queue(&mut compliance, mint, ComplianceAction::RemoveTradingWindow { index: 1 }, queued_by, NOW)
+            .unwrap();
+        assert_eq!(next.id, 1);
+        assert_eq!(compliance.queued_change_count, 2);
+    }
+
+    #[test]
+    fn queue_rejects_oversized_modules() {
+        let mut compliance = registry(DELAY);
+        let mint = compliance.mint;
+        let action = |parameters: Vec<u8>| ComplianceAction::AddComplianceModule {
+            name: "module".to_string(),
+            module_type: ComplianceModuleType::Custom,
+            program_id: Pubkey::new_unique(),
+            account_count: 0,
+            parameters,
+        };
+
+        assert!(change()
+            .queue(&mut compliance, mint, action(vec![0; 257]), Pubkey::new_unique(), NOW)
+            .is_err());
+        assert!(change()
+            .queue(&mut compliance, mint, action(vec![0; 256]), Pubkey::new_unique(), NOW)
+            .is_ok());
+        assert!(action(vec![0; 256]).try_to_vec().unwrap().len() <= ComplianceAction::LEN);
+    }
+}
@@ -1,20 +1,54 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        self,
+        extension::{transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions},
+    },
+    token_interface::{thaw_account, ThawAccount, TokenAccount},
+};
 use crate::{error::ERC3643Error, state::*};
 
-/// Utility functions for the ERC-3643 program
+// Utility functions for the ERC-3643 program
+/// State consulted by `validate_transfer_compliance`
+pub struct ComplianceAccounts<'a> {
+    pub config: &'a TokenConfig,
+    pub compliance: &'a ComplianceRegistry,
+    pub from_identity: Option<&'a IdentityRegistry>,
+    pub to_identity: &'a IdentityRegistry,
+    pub from_frozen: Option<&'a FrozenAccount>,
+    pub to_frozen: Option<&'a FrozenAccount>,
+    pub from_counter: Option<&'a TransferCounter>,
+}
 
 /// Validate that a transfer is compliant with all rules
+///
+/// Balances are the pre-transfer amounts held by the source and destination
+/// token accounts; `supply` is the mint supply once the transfer completes.
 pub fn validate_transfer_compliance(
     from: &Pubkey,
     to: &Pubkey,
     amount: u64,
-    config: &TokenConfig,
-    from_identity: Option<&IdentityRegistry>,
-    to_identity: &IdentityRegistry,
-    compliance: &ComplianceRegistry,
-    from_frozen: Option<&FrozenAccount>,
-    to_frozen: Option<&FrozenAccount>,
+    from_balance: u64,
+    to_balance: u64,
+    supply: u64,
+    accounts: &ComplianceAccounts,
 ) -> Result<bool> {
+    let ComplianceAccounts {
+        config,
+        compliance,
+        from_identity,
+        to_identity,
+        from_frozen,
+        to_frozen,
+        from_counter,
+    } = *accounts;
+
+
     // Check if token is paused
     if config.paused {
         return Ok(false);
@@ -33,6 +67,14 @@ pub fn validate_transfer_compliance(
         }
     }
 
+    // Partially frozen tokens cannot be spent
+    let frozen_amount = from_frozen.map_or(0, |info| info.get_frozen_amount());
+    let spendable_balance = from_balance.saturating_sub(frozen_amount);
+
+    if amount > spendable_balance && frozen_amount > 0 {
+        return Ok(false);
+    }
+
     // Check identity verification for recipient
     if !to_identity.is_verified {
         return Ok(false);
@@ -57,24 +99,207 @@ pub fn validate_transfer_compliance(
     };
     let to_country = to_identity.country;
 
-    // Simulate token balances (in real implementation, these would be fetched from token accounts)
-    let from_balance = 1000; // Placeholder
-    let to_balance = 500;    // Placeholder
-
     // Check compliance
     let compliance_result = compliance.check_transfer_compliance(
-        from,
-        to,
-        amount,
-        from_balance,
-        to_balance,
-        from_country,
-        to_country,
+        &TransferCheck {
+            from: *from,
+            to: *to,
+            amount,
+            from_balance: spendable_balance,
+            to_balance,
+            supply,
+            from_country,
+            to_country,
+        },
+        from_counter,
     )?;
 
     Ok(compliance_result.allowed)
 }
 
+/// Transfer tokens with the config PDA signing as the mint's permanent delegate.
+/// The transfer hook's extra accounts are resolved from `additional_accounts`.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_as_permanent_delegate<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    additional_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from.clone(),
+        mint.clone(),
+        to.clone(),
+        config.clone(),
+        additional_accounts,
+        amount,
+        decimals,
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Check whether Token-2022 is in the middle of a transfer involving this
+/// token account, i.e. the transfer hook is executing and balances have
+/// already been updated.
+pub fn is_transferring(token_account: &AccountInfo) -> Result<bool> {
+    let data = token_account.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(state
+        .get_extension::<TransferHookAccount>()
+        .is_ok_and(|extension| bool::from(extension.transferring)))
+}
+
+/// Thaw a token account left frozen by the mint's DefaultAccountState
+/// extension, with the config PDA signing as freeze authority.
+pub fn thaw_if_frozen<'info>(
+    token_program: &AccountInfo<'info>,
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if token_account.is_frozen() {
+        thaw_account(CpiContext::new_with_signer(
+            token_program.clone(),
+            ThawAccount {
+                account: token_account.to_account_info(),
+                mint: mint.clone(),
+                authority: config.clone(),
+            },
+            signer_seeds,
+        ))?;
+    }
+    Ok(())
+}
+
+/// Create a PDA owned by this program from an account passed as a remaining
+/// account, unless it already exists. Returns whether it was created.
+pub fn create_pda_if_needed<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> Result<bool> {
+    let (expected, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(account.key(), expected, ERC3643Error::InvalidArgument);
+    if account.owner == &crate::ID {
+        return Ok(false);
+    }
+
+    let bump = [bump];
+    let mut pda_seeds = seeds.to_vec();
+    pda_seeds.push(&bump);
+    let signer_seeds = &[&pda_seeds[..]];
+
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = account.lamports();
+    if lamports == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Someone already funded the address: top up, allocate and assign
+        if rent > lamports {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: account.clone(),
+                    },
+                ),
+                rent - lamports,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Allocate {
+                    account_to_allocate: account.clone(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Assign {
+                    account_to_assign: account.clone(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    Ok(true)
+}
+
+/// Read a program account passed as a remaining account. A freshly created
+/// account has no discriminator yet and reads as zeroed.
+pub fn read_program_account<T: AccountDeserialize + Owner>(
+    account: &AccountInfo,
+    created: bool,
+) -> Result<T> {
+    require_keys_eq!(*account.owner, T::owner(), ERC3643Error::InvalidArgument);
+    let data = account.try_borrow_data()?;
+    if created {
+        T::try_deserialize_unchecked(&mut &data[..])
+    } else {
+        T::try_deserialize(&mut &data[..])
+    }
+}
+
+/// Read a program PDA passed as a remaining account that may not exist yet
+pub fn read_optional_account<T: AccountDeserialize + Owner>(
+    account: &AccountInfo,
+    seeds: &[&[u8]],
+) -> Result<Option<T>> {
+    let (expected, _) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(account.key(), expected, ERC3643Error::InvalidArgument);
+    if account.owner != &crate::ID || account.data_is_empty() {
+        return Ok(None);
+    }
+    read_program_account(account, false).map(Some)
+}
+
+/// Write back a program account read with `read_program_account`
+pub fn write_program_account<T: AccountSerialize>(account: &AccountInfo, value: &T) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])
+}
+
+/// Read a token account of `mint` passed as a remaining account
+pub fn read_token_account(
+    account: &AccountInfo,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<TokenAccount> {
+    require_keys_eq!(*account.owner, *token_program, ERC3643Error::InvalidArgument);
+    let token_account = TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    require_keys_eq!(token_account.mint, *mint, ERC3643Error::InvalidArgument);
+    Ok(token_account)
+}
+
 /// Calculate the required space for a dynamic account
 pub fn calculate_dynamic_account_space(
     base_size: usize,
@@ -91,31 +316,160 @@ pub fn calculate_dynamic_account_space(
 }
 
 /// Verify that all required claim topics are covered by valid claims
+///
+/// A claim counts towards a topic when it belongs to the identity, has not
+/// expired or been revoked, and its issuer is trusted for that topic.
 pub fn verify_required_claims_coverage(
     identity: &Pubkey,
+    claims: &[Claim],
     required_topics: &[u64],
     trusted_issuers: &TrustedIssuersRegistry,
-    // In a real implementation, we would also pass the claims for this identity
-) -> Result<bool> {
-    // This is a simplified version - in a real implementation we would:
-    // 1. Fetch all claims for the identity
-    // 2. Validate each claim's signature and expiration
-    // 3. Check that the issuer is trusted for the claim topic
-    // 4. Ensure all required topics are covered
-
-    if required_topics.is_empty() {
-        return Ok(true);
+) -> IdentityVerificationResult {
+    let now = Clock::get().unwrap().unix_timestamp;
+    verify_required_claims_coverage_at(identity, claims, required_topics, trusted_issuers, now)
+}
+
+/// Verify claim coverage at the given time
+pub fn verify_required_claims_coverage_at(
+    identity: &Pubkey,
+    claims: &[Claim],
+    required_topics: &[u64],
+    trusted_issuers: &TrustedIssuersRegistry,
+    now: i64,
+) -> IdentityVerificationResult {
+    let mut result = IdentityVerificationResult::new_at(*identity, now);
+
+    for claim in claims {
+        result.claims_checked += 1;
+
+        let is_valid = claim.identity == *identity
+            && claim.is_valid_at(now)
+            && trusted_issuers.is_trusted_for_topic(&claim.issuer, claim.topic);
+        if !is_valid {
+            continue;
+        }
+
+        if required_topics.contains(&claim.topic) {
+            result.add_valid_claim(claim.topic);
+        } else {
+            result.valid_claims += 1;
+        }
     }
 
-    // For now, we'll return true if there are trusted issuers for all required topics
-    for topic in required_topics {
-        let issuers_for_topic = trusted_issuers.get_issuers_for_topic(*topic);
-        if issuers_for_topic.is_empty() {
-            return Ok(false);
+    result.finalize(required_topics);
+    result
+}
+
+/// Load the claim accounts of an identity passed as remaining accounts,
+/// checking ownership and PDA derivation of each
+pub fn load_identity_claims(accounts: &[AccountInfo], identity: &Pubkey) -> Result<Vec<Claim>> {
+    let mut claims = Vec::with_capacity(accounts.len());
+
+    for account in accounts {
+        validate_account_ownership(account, &crate::ID)?;
+        let claim = Claim::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+
+        require!(claim.identity == *identity, ERC3643Error::InvalidArgument);
+        validate_pda_derivation(
+            account.key,
+            &[
+                CLAIM_SEED,
+                identity.as_ref(),
+                claim.issuer.as_ref(),
+                &claim.topic.to_le_bytes(),
+            ],
+            &crate::ID,
+        )?;
+
+        claims.push(claim);
+    }
+
+    Ok(claims)
+}
+
+/// Split remaining accounts into the leading claim accounts and the rest
+pub fn split_claim_accounts<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+) -> (&'a [AccountInfo<'info>], &'a [AccountInfo<'info>]) {
+    let claim_count = accounts
+        .iter()
+        .take_while(|account| {
+            account.owner == &crate::ID
+                && account
+                    .try_borrow_data()
+                    .is_ok_and(|data| data.starts_with(&Claim::DISCRIMINATOR))
+        })
+        .count();
+    accounts.split_at(claim_count)
+}
+
+/// Unverify the identity's registries of other tokens that `claims` no
+/// longer cover for `topic`
+///
+/// `accounts` holds, for each other token, the identity registry followed by
+/// the token's trusted issuers and claim topics registries. Every registry
+/// bound to `storage` besides the one of `mint` must be included, since the
+/// claims are shared by all of them.
+pub fn unverify_uncovered_registries(
+    accounts: &[AccountInfo],
+    storage_key: &Pubkey,
+    storage: &IdentityStorage,
+    mint: &Pubkey,
+    claims: &[Claim],
+    topic: u64,
+) -> Result<()> {
+    let groups = accounts.chunks_exact(3);
+    require!(groups.remainder().is_empty(), ERC3643Error::InvalidArgument);
+
+    let mut mints = vec![*mint];
+    let mut bound: u32 = 1;
+    for group in groups {
+        let (registry_info, issuers_info, topics_info) = (&group[0], &group[1], &group[2]);
+
+        let mut registry: IdentityRegistry = read_program_account(registry_info, false)?;
+        validate_pda_derivation(
+            registry_info.key,
+            &[IDENTITY_SEED, registry.mint.as_ref(), registry.user.as_ref()],
+            &crate::ID,
+        )?;
+        require!(registry.identity == storage.identity, ERC3643Error::InvalidArgument);
+        require!(!mints.contains(&registry.mint), ERC3643Error::InvalidArgument);
+        require!(registry_info.is_writable, ERC3643Error::InvalidArgument);
+        mints.push(registry.mint);
+        if registry.storage == *storage_key {
+            bound += 1;
+        }
+
+        let trusted_issuers: TrustedIssuersRegistry = read_program_account(issuers_info, false)?;
+        validate_pda_derivation(
+            issuers_info.key,
+            &[TRUSTED_ISSUERS_SEED, registry.mint.as_ref()],
+            &crate::ID,
+        )?;
+        let claim_topics: ClaimTopicsRegistry = read_program_account(topics_info, false)?;
+        validate_pda_derivation(
+            topics_info.key,
+            &[CLAIM_TOPICS_SEED, registry.mint.as_ref()],
+            &crate::ID,
+        )?;
+
+        if !claim_topics.is_topic_required(topic) || !registry.is_verified {
+            continue;
+        }
+
+        let coverage = verify_required_claims_coverage(&registry.identity, claims, &[topic], &trusted_issuers);
+        if !coverage.is_verified {
+            registry.update_required_claims_status(false)?;
+            registry.update_verification(false)?;
+            write_program_account(registry_info, &registry)?;
+
+            msg!("Identity unverified for mint: {}", registry.mint);
         }
     }
 
-    Ok(true)
+    require!(bound == storage.bound_registries, ERC3643Error::IdentityStorageMismatch);
+
+    Ok(())
 }
 
 /// Generate a deterministic seed for PDA derivation
@@ -145,6 +499,95 @@ pub fn validate_claim_data(
     Ok(())
 }
 
+/// Verify that a claim was signed by its issuer. `message` is the claim's
+/// `Claim::signed_message`.
+///
+/// - `Ed25519`: the instruction preceding this one must be an Ed25519 program
+///   instruction verifying `signature` by `issuer` over the claim message
+/// - `ECDSA`: the issuer key must be the EVM address recovered from the
+///   signature (see `Claim::recover_ecdsa_issuer`)
+/// - `Contract`: the issuer account must sign the transaction
+pub fn verify_claim_signature(
+    scheme: &ClaimScheme,
+    issuer: &AccountInfo,
+    message: &[u8],
+    signature: &[u8],
+    instructions_sysvar: &AccountInfo,
+) -> Result<()> {
+    match scheme {
+        ClaimScheme::Ed25519 => verify_ed25519_instruction(
+            instructions_sysvar,
+            issuer.key,
+            message,
+            signature,
+        ),
+        ClaimScheme::ECDSA => {
+            let recovered = Claim::recover_ecdsa_issuer(message, signature)?;
+            require!(recovered == *issuer.key, ERC3643Error::InvalidClaimSignature);
+            Ok(())
+        }
+        ClaimScheme::Contract => {
+            require!(issuer.is_signer, ERC3643Error::IssuerRoleRequired);
+            Ok(())
+        }
+        ClaimScheme::RSA => Err(ERC3643Error::UnsupportedClaimScheme.into()),
+    }
+}
+
+/// Check that the instruction before the current one is an Ed25519 program
+/// instruction verifying exactly `signature` by `signer` over `message`
+fn verify_ed25519_instruction(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_SIZE: usize = 14;
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, ERC3643Error::MissingSignatureVerification);
+
+    let instruction = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    require!(
+        instruction.program_id == ed25519_program::ID,
+        ERC3643Error::MissingSignatureVerification
+    );
+
+    let ix_data = &instruction.data;
+    require!(
+        ix_data.len() >= OFFSETS_START + OFFSETS_SIZE && ix_data[0] == 1,
+        ERC3643Error::InvalidClaimSignature
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([ix_data[at], ix_data[at + 1]]);
+    let signature_offset = read_u16(OFFSETS_START) as usize;
+    let signature_ix_index = read_u16(OFFSETS_START + 2);
+    let public_key_offset = read_u16(OFFSETS_START + 4) as usize;
+    let public_key_ix_index = read_u16(OFFSETS_START + 6);
+    let message_offset = read_u16(OFFSETS_START + 8) as usize;
+    let message_size = read_u16(OFFSETS_START + 10) as usize;
+    let message_ix_index = read_u16(OFFSETS_START + 12);
+
+    // All verified data must live in the Ed25519 instruction itself
+    require!(
+        signature_ix_index == u16::MAX
+            && public_key_ix_index == u16::MAX
+            && message_ix_index == u16::MAX,
+        ERC3643Error::InvalidClaimSignature
+    );
+
+    let slice = |offset: usize, len: usize| ix_data.get(offset..offset + len);
+    require!(
+        slice(public_key_offset, 32) == Some(signer.as_ref())
+            && slice(signature_offset, 64) == Some(signature)
+            && slice(message_offset, message_size) == Some(message),
+        ERC3643Error::InvalidClaimSignature
+    );
+
+    Ok(())
+}
+
 /// Check if a timestamp is within a valid range
 pub fn is_timestamp_valid(timestamp: i64, max_age_seconds: i64) -> bool {
     let now = Clock::get().unwrap().unix_timestamp;
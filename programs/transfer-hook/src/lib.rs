@@ -1,12 +1,6 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{
-    token_2022::Token2022,
-    token_interface::{Mint, TokenAccount},
-};
-use spl_transfer_hook_interface::{
-    instruction::{ExecuteInstruction, TransferHookInstruction},
-    get_extra_account_metas_address, get_extra_account_metas_address_and_bump_seed,
-};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use spl_transfer_hook_interface::instruction::{ExecuteInstruction, TransferHookInstruction};
 use spl_tlv_account_resolution::{
     account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
 };
@@ -26,103 +20,7 @@ pub mod transfer_hook {
     pub fn initialize_extra_account_meta_list(
         ctx: Context<InitializeExtraAccountMetaList>,
     ) -> Result<()> {
-        // Define the extra accounts needed for compliance checking
-        let account_metas = vec![
-            // ERC3643 program
-            ExtraAccountMeta::new_with_pubkey(&erc3643_anchor::ID, false, false)?,
-            
-            // Token config PDA
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"config".to_vec(),
-                    },
-                    Seed::AccountKey { index: 0 }, // mint
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-            
-            // From identity registry PDA (optional for minting)
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"identity".to_vec(),
-                    },
-                    Seed::AccountKey { index: 1 }, // from token account owner
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-            
-            // To identity registry PDA
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"identity".to_vec(),
-                    },
-                    Seed::AccountKey { index: 2 }, // to token account owner
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-            
-            // Compliance registry PDA
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"compliance".to_vec(),
-                    },
-                    Seed::AccountKey { index: 0 }, // mint
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-            
-            // Claim topics registry PDA
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"claim_topics".to_vec(),
-                    },
-                    Seed::AccountKey { index: 0 }, // mint
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-            
-            // From identity (for PDA derivation)
-            ExtraAccountMeta::new_with_pubkey(&Pubkey::default(), false, false)?,
-            
-            // To identity (for PDA derivation)
-            ExtraAccountMeta::new_with_pubkey(&Pubkey::default(), false, false)?,
-            
-            // From frozen account PDA (optional)
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"frozen".to_vec(),
-                    },
-                    Seed::AccountKey { index: 0 }, // mint
-                    Seed::AccountKey { index: 1 }, // from token account owner
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-            
-            // To frozen account PDA (optional)
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal {
-                        bytes: b"frozen".to_vec(),
-                    },
-                    Seed::AccountKey { index: 0 }, // mint
-                    Seed::AccountKey { index: 2 }, // to token account owner
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-        ];
+        let account_metas = base_account_metas()?;
 
         // Initialize the extra account meta list
         let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
@@ -131,7 +29,7 @@ pub mod transfer_hook {
         let mint = ctx.accounts.mint.key();
         let signer_seeds: &[&[&[u8]]] = &[&[
             b"extra-account-metas",
-            &mint.as_ref(),
+            mint.as_ref(),
             &[ctx.bumps.extra_account_meta_list],
         ]];
 
@@ -159,8 +57,62 @@ pub mod transfer_hook {
         Ok(())
     }
 
+    /// Rewrite the extra account meta list with the accounts of the mint's
+    /// custom compliance modules, listed after the base compliance accounts
+    /// and the module authority in the order the compliance registry holds
+    /// the modules
+    pub fn update_extra_account_meta_list(
+        ctx: Context<UpdateExtraAccountMetaList>,
+        module_metas: Vec<ModuleAccountMeta>,
+    ) -> Result<()> {
+        let mut account_metas = base_account_metas()?;
+        if !module_metas.is_empty() {
+            account_metas.push(module_authority_meta()?);
+        }
+        for meta in &module_metas {
+            account_metas.push(meta.to_extra_account_meta()?);
+        }
+
+        let list = ctx.accounts.extra_account_meta_list.to_account_info();
+        let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
+        let current_size = list.data_len();
+
+        // Grow before rewriting, shrink after
+        if account_size > current_size {
+            let lamports = Rent::get()?
+                .minimum_balance(account_size)
+                .saturating_sub(list.lamports());
+            if lamports > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.authority.to_account_info(),
+                            to: list.clone(),
+                        },
+                    ),
+                    lamports,
+                )?;
+            }
+            list.realloc(account_size, false)?;
+        }
+
+        ExtraAccountMetaList::update::<ExecuteInstruction>(
+            &mut list.try_borrow_mut_data()?,
+            &account_metas,
+        )?;
+
+        if account_size < current_size {
+            list.realloc(account_size, false)?;
+        }
+
+        msg!("Extra account meta list updated with {} module accounts", module_metas.len());
+
+        Ok(())
+    }
+
     /// Execute the transfer hook - this is called by Token-2022 on every transfer
-    pub fn execute(ctx: Context<Execute>, amount: u64) -> Result<()> {
+    pub fn execute<'info>(ctx: Context<'_, '_, '_, 'info, Execute<'info>>, amount: u64) -> Result<()> {
         let mint = &ctx.accounts.mint;
         let from_token_account = &ctx.accounts.source_token;
         let to_token_account = &ctx.accounts.destination_token;
@@ -171,15 +123,12 @@ pub mod transfer_hook {
 
         msg!("Transfer hook executing: {} tokens from {} to {}", amount, from, to);
 
-        // Check if this is a mint operation (from is zero address equivalent)
-        let is_mint = from_token_account.amount == 0 && amount > 0;
-        
-        // Check if this is a burn operation (to is zero address equivalent)
-        let is_burn = to_token_account.amount == 0 && amount > 0;
-
-        // Skip compliance check for burn operations (handled by burn instruction)
-        if is_burn {
-            msg!("Skipping compliance check for burn operation");
+        // Skip compliance check for agent operations signed by the config PDA
+        // as permanent delegate (forced transfers, recoveries)
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[b"config", mint.key().as_ref()], &erc3643_anchor::ID);
+        if ctx.accounts.authority.key() == config_pda {
+            msg!("Skipping compliance check for permanent delegate transfer");
             return Ok(());
         }
 
@@ -188,21 +137,20 @@ pub mod transfer_hook {
         let cpi_accounts = CheckTransferAllowed {
             config: ctx.accounts.config.to_account_info(),
             mint: mint.to_account_info(),
-            from_identity_registry: if is_mint {
-                None
-            } else {
-                Some(ctx.accounts.from_identity_registry.to_account_info())
-            },
+            from_token_account: Some(from_token_account.to_account_info()),
+            to_token_account: to_token_account.to_account_info(),
+            from_identity_registry: initialized(&ctx.accounts.from_identity_registry),
             to_identity_registry: ctx.accounts.to_identity_registry.to_account_info(),
             compliance_registry: ctx.accounts.compliance_registry.to_account_info(),
             claim_topics_registry: ctx.accounts.claim_topics_registry.to_account_info(),
-            from_identity: ctx.accounts.from_identity.to_account_info(),
-            to_identity: ctx.accounts.to_identity.to_account_info(),
-            from_frozen_account: Some(ctx.accounts.from_frozen_account.to_account_info()),
-            to_frozen_account: Some(ctx.accounts.to_frozen_account.to_account_info()),
+            from_frozen_account: initialized(&ctx.accounts.from_frozen_account),
+            to_frozen_account: initialized(&ctx.accounts.to_frozen_account),
+            from_transfer_counter: initialized(&ctx.accounts.from_transfer_counter),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        // Compliance module accounts follow the base extra accounts
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
         let transfer_allowed = check_transfer_allowed(cpi_ctx, from, to, amount)?;
 
         // Reject transfer if not compliant
@@ -215,14 +163,211 @@ pub mod transfer_hook {
         Ok(())
     }
 
-    /// Fallback function for any other transfer hook instructions
+    /// Fallback routing the transfer hook interface's Execute instruction,
+    /// which Token-2022 invokes with its own discriminator
     pub fn fallback<'info>(
-        _program_id: &Pubkey,
-        _accounts: &'info [AccountInfo<'info>],
-        _data: &[u8],
+        program_id: &Pubkey,
+        accounts: &'info [AccountInfo<'info>],
+        data: &[u8],
     ) -> Result<()> {
-        msg!("Transfer hook fallback called");
-        Ok(())
+        match TransferHookInstruction::unpack(data)? {
+            TransferHookInstruction::Execute { amount } => {
+                let amount_bytes = amount.to_le_bytes();
+                __private::__global::execute(program_id, accounts, &amount_bytes)
+            }
+            _ => Err(ProgramError::InvalidInstructionData.into()),
+        }
+    }
+}
+
+/// Index of the ERC3643 program in the Execute accounts list
+const ERC3643_PROGRAM_INDEX: u8 = 5;
+
+/// Extra accounts needed for compliance checking, before any compliance
+/// module accounts
+fn base_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    // Define the extra accounts needed for compliance checking. Indices
+    // 0-4 are source, mint, destination, authority and this list; the
+    // ERC3643 program is index 5 and owns every PDA below
+    Ok(vec![
+        // ERC3643 program
+        ExtraAccountMeta::new_with_pubkey(&erc3643_anchor::ID, false, false)?,
+        
+        // Token config PDA
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"config".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+        
+        // From identity registry PDA
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"identity".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+                source_owner_seed(),
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+        
+        // To identity registry PDA
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"identity".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+                destination_owner_seed(),
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+        
+        // Compliance registry PDA (investor count is updated)
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"compliance".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+            ],
+            false, // is_signer
+            true,  // is_writable
+        )?,
+        
+        // Claim topics registry PDA
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"claim_topics".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+        
+        // From frozen account PDA (optional)
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"frozen".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+                source_owner_seed(),
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+        
+        // To frozen account PDA (optional)
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"frozen".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+                destination_owner_seed(),
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+        
+        // From transfer counter PDA (written by the compliance check)
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            ERC3643_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: b"transfer_counter".to_vec(),
+                },
+                Seed::AccountKey { index: 1 }, // mint
+                source_owner_seed(),
+            ],
+            false, // is_signer
+            true,  // is_writable
+        )?,
+    ])
+}
+
+/// Module authority PDA signing the ERC3643 program's calls into the
+/// compliance modules
+fn module_authority_meta() -> Result<ExtraAccountMeta> {
+    Ok(ExtraAccountMeta::new_external_pda_with_seeds(
+        ERC3643_PROGRAM_INDEX,
+        &[
+            Seed::Literal {
+                bytes: MODULE_AUTHORITY_SEED.to_vec(),
+            },
+            Seed::AccountKey { index: 1 }, // mint
+        ],
+        false, // is_signer
+        false, // is_writable
+    )?)
+}
+
+/// Extra account meta of a compliance module account, in the encoding of
+/// `ExtraAccountMeta`: discriminator 0 for a fixed address, 1 for a PDA of
+/// this program and 128 + index for a PDA of the program at that account
+/// index, with the packed seeds as `address_config`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ModuleAccountMeta {
+    pub discriminator: u8,
+    pub address_config: [u8; 32],
+    pub is_writable: bool,
+}
+
+impl ModuleAccountMeta {
+    fn to_extra_account_meta(&self) -> Result<ExtraAccountMeta> {
+        let mut meta = ExtraAccountMeta::new_with_pubkey(
+            &Pubkey::new_from_array(self.address_config),
+            false,
+            self.is_writable,
+        )?;
+        meta.discriminator = self.discriminator;
+        Ok(meta)
+    }
+}
+
+/// Optional ERC3643 PDAs (identity, freeze info, counters) only exist once created, so
+/// pass them as absent rather than failing deserialization in the CPI
+fn initialized<'info>(account: &UncheckedAccount<'info>) -> Option<AccountInfo<'info>> {
+    if account.owner == &erc3643_anchor::ID && !account.data_is_empty() {
+        Some(account.to_account_info())
+    } else {
+        None
+    }
+}
+
+/// Owner of the source token account, read from its data
+fn source_owner_seed() -> Seed {
+    Seed::AccountData {
+        account_index: 0,
+        data_index: 32,
+        length: 32,
+    }
+}
+
+/// Owner of the destination token account, read from its data
+fn destination_owner_seed() -> Seed {
+    Seed::AccountData {
+        account_index: 2,
+        data_index: 32,
+        length: 32,
     }
 }
 
@@ -247,13 +392,51 @@ pub struct InitializeExtraAccountMetaList<'info> {
 }
 
 #[derive(Accounts)]
-pub struct Execute<'info> {
-    /// The mint being transferred
+pub struct UpdateExtraAccountMetaList<'info> {
+    /// Owner or compliance manager of the token, pays for any growth
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// ERC3643 token configuration of the mint
+    #[account(
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        seeds::program = erc3643_anchor::ID
+    )]
+    pub config: Account<'info, TokenConfig>,
+
+    /// The mint the extra account meta list belongs to
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// ERC3643 role registry of the mint
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+        seeds::program = erc3643_anchor::ID,
+        constraint = role_registry.has_role(&config, &authority.key(), Role::ComplianceManager) @ TransferHookError::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    /// The extra account meta list PDA
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    /// CHECK: Rewritten by the instruction
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
     /// Source token account
     pub source_token: InterfaceAccount<'info, TokenAccount>,
 
+    /// The mint being transferred
+    pub mint: InterfaceAccount<'info, Mint>,
+
     /// Destination token account
     pub destination_token: InterfaceAccount<'info, TokenAccount>,
 
@@ -263,6 +446,10 @@ pub struct Execute<'info> {
 
     /// Extra account meta list
     /// CHECK: This is validated by the transfer hook interface
+    #[account(
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
     pub extra_account_meta_list: UncheckedAccount<'info>,
 
     // Extra accounts for compliance checking
@@ -283,20 +470,13 @@ pub struct Execute<'info> {
 
     /// Compliance registry PDA
     /// CHECK: This is validated by the ERC3643 program
+    #[account(mut)]
     pub compliance_registry: UncheckedAccount<'info>,
 
     /// Claim topics registry PDA
     /// CHECK: This is validated by the ERC3643 program
     pub claim_topics_registry: UncheckedAccount<'info>,
 
-    /// From identity (for PDA derivation)
-    /// CHECK: Used for PDA derivation only
-    pub from_identity: UncheckedAccount<'info>,
-
-    /// To identity (for PDA derivation)
-    /// CHECK: Used for PDA derivation only
-    pub to_identity: UncheckedAccount<'info>,
-
     /// From frozen account PDA
     /// CHECK: This is validated by the ERC3643 program
     pub from_frozen_account: UncheckedAccount<'info>,
@@ -304,6 +484,11 @@ pub struct Execute<'info> {
     /// To frozen account PDA
     /// CHECK: This is validated by the ERC3643 program
     pub to_frozen_account: UncheckedAccount<'info>,
+
+    /// From transfer counter PDA
+    /// CHECK: This is validated by the ERC3643 program
+    #[account(mut)]
+    pub from_transfer_counter: UncheckedAccount<'info>,
 }
 
 #[error_code]
@@ -319,4 +504,7 @@ pub enum TransferHookError {
     
     #[msg("Compliance check failed")]
     ComplianceCheckFailed,
+
+    #[msg("Owner or compliance manager role required")]
+    Unauthorized,
 }